@@ -0,0 +1,278 @@
+//! Tracking of alerts from `MetagameEvent` events.
+
+use std::collections::HashMap;
+
+use faction::Faction;
+use handler::EventHandler;
+use response::{MetagameEvent, MetagameEventState};
+use zone::ZoneId;
+use {Id, MetagameEventId, Timestamp, WorldId};
+
+/// The territory control of each faction in percent.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Territory {
+    pub vs: f64,
+    pub nc: f64,
+    pub tr: f64,
+}
+
+impl Territory {
+    pub fn get(&self, faction: Faction) -> f64 {
+        match faction {
+            Faction::Vs => self.vs,
+            Faction::Nc => self.nc,
+            Faction::Tr => self.tr,
+            Faction::Nso => 0.0,
+        }
+    }
+
+    /// The faction with the most territory, `None` on a tie.
+    pub fn leader(&self) -> Option<Faction> {
+        let mut leader = None;
+        let mut most = 0.0;
+        for &faction in &[Faction::Vs, Faction::Nc, Faction::Tr] {
+            let territory = self.get(faction);
+            if territory > most {
+                leader = Some(faction);
+                most = territory;
+            } else if territory == most {
+                leader = None;
+            }
+        }
+
+        leader
+    }
+}
+
+impl<'a> From<&'a MetagameEvent> for Territory {
+    fn from(event: &'a MetagameEvent) -> Territory {
+        Territory {
+            vs: event.faction_vs,
+            nc: event.faction_nc,
+            tr: event.faction_tr,
+        }
+    }
+}
+
+/// A running alert.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Alert {
+    pub world_id: WorldId,
+    pub zone_id: ZoneId,
+    pub instance_id: Id,
+    pub metagame_event_id: MetagameEventId,
+    pub start: Timestamp,
+    pub experience_bonus: f64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AlertStarted {
+    pub alert: Alert,
+    /// Whether the alert was restarted, e.g. after a server restart.
+    pub restarted: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AlertEnded {
+    pub world_id: WorldId,
+    pub zone_id: ZoneId,
+    pub instance_id: Id,
+    pub metagame_event_id: MetagameEventId,
+    /// The start, unknown if the alert started before tracking.
+    pub start: Option<Timestamp>,
+    pub end: Timestamp,
+    pub canceled: bool,
+    /// The territory control at the end.
+    pub territory: Territory,
+    /// The faction with the most territory, `None` for canceled alerts and ties.
+    pub winner: Option<Faction>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum AlertNotification {
+    Started(AlertStarted),
+    Ended(AlertEnded),
+}
+
+/// Keeps the running alerts per world and zone.
+///
+/// Alerts start with the `started` and `restarted` states and end with `canceled` and `ended`.
+/// Instance ids are only unique per zone, so alerts are told apart by world, zone and instance.
+/// Notifications about alerts starting and ending are kept until taken with `drain`.
+#[derive(Clone, Debug, Default)]
+pub struct AlertTracker {
+    active: HashMap<(WorldId, ZoneId, Id), Alert>,
+    notifications: Vec<AlertNotification>,
+}
+
+impl AlertTracker {
+    pub fn new() -> AlertTracker {
+        AlertTracker::default()
+    }
+
+    pub fn handle_metagame_event(&mut self, event: &MetagameEvent) {
+        let key = (event.world_id, event.zone_id, event.instance_id);
+        let state = match event.state() {
+            Some(state) => state,
+            None => return,
+        };
+
+        match state {
+            MetagameEventState::Started | MetagameEventState::Restarted => {
+                if state == MetagameEventState::Started && self.active.contains_key(&key) {
+                    return;
+                }
+
+                let alert = Alert {
+                    world_id: event.world_id,
+                    zone_id: event.zone_id,
+                    instance_id: event.instance_id,
+                    metagame_event_id: event.metagame_event_id,
+                    start: event.timestamp,
+                    experience_bonus: event.experience_bonus,
+                };
+                let alert = self.active.entry(key).or_insert(alert).clone();
+                self.notifications
+                    .push(AlertNotification::Started(AlertStarted {
+                        alert,
+                        restarted: state == MetagameEventState::Restarted,
+                    }));
+            }
+            MetagameEventState::Canceled | MetagameEventState::Ended => {
+                let alert = self.active.remove(&key);
+                let canceled = state == MetagameEventState::Canceled;
+                let territory = Territory::from(event);
+
+                self.notifications.push(AlertNotification::Ended(AlertEnded {
+                    world_id: event.world_id,
+                    zone_id: event.zone_id,
+                    instance_id: event.instance_id,
+                    metagame_event_id: event.metagame_event_id,
+                    start: alert.map(|alert| alert.start),
+                    end: event.timestamp,
+                    canceled,
+                    territory,
+                    winner: if canceled { None } else { territory.leader() },
+                }));
+            }
+            MetagameEventState::ExperienceBonusChanged => {
+                if let Some(alert) = self.active.get_mut(&key) {
+                    alert.experience_bonus = event.experience_bonus;
+                }
+            }
+        }
+    }
+
+    /// The running alerts of a world.
+    pub fn active(&self, world_id: WorldId) -> impl Iterator<Item = &Alert> {
+        self.active
+            .values()
+            .filter(move |alert| alert.world_id == world_id)
+    }
+
+    /// The running alert of a zone instance on a world, if any.
+    pub fn active_in(&self, world_id: WorldId, zone_id: ZoneId) -> Option<&Alert> {
+        self.active(world_id).find(|alert| alert.zone_id == zone_id)
+    }
+
+    /// Takes the notifications so far, oldest first.
+    pub fn drain(&mut self) -> ::std::vec::Drain<'_, AlertNotification> {
+        self.notifications.drain(..)
+    }
+}
+
+impl EventHandler for AlertTracker {
+    fn on_metagame_event(&mut self, event: &MetagameEvent) {
+        self.handle_metagame_event(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::metagame_event;
+    use zone::Zone;
+
+    #[test]
+    fn lifecycle() {
+        let esamir = ZoneId::from(Zone::Esamir);
+        let mut tracker = AlertTracker::new();
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Started, esamir, 100));
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Started, esamir, 100));
+
+        let alert = tracker
+            .active_in(WorldId::new(10), esamir)
+            .unwrap();
+        assert_eq!(alert.instance_id, 24789);
+        assert_eq!(alert.start, Timestamp::from_secs(100));
+
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Ended, esamir, 5500));
+        assert_eq!(tracker.active(WorldId::new(10)).count(), 0);
+
+        let notifications: Vec<_> = tracker.drain().collect();
+        assert_eq!(notifications.len(), 2);
+        match notifications[1] {
+            AlertNotification::Ended(ref ended) => {
+                assert_eq!(ended.start, Some(Timestamp::from_secs(100)));
+                assert_eq!(ended.end, Timestamp::from_secs(5500));
+                assert!(!ended.canceled);
+                assert_eq!(ended.winner, Some(Faction::Vs));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn canceled() {
+        let esamir = ZoneId::from(Zone::Esamir);
+        let mut tracker = AlertTracker::new();
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Restarted, esamir, 100));
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Canceled, esamir, 200));
+
+        let notifications: Vec<_> = tracker.drain().collect();
+        match notifications[..] {
+            [AlertNotification::Started(ref started), AlertNotification::Ended(ref ended)] => {
+                assert!(started.restarted);
+                assert!(ended.canceled);
+                assert_eq!(ended.winner, None);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn simultaneous_zones() {
+        let esamir = ZoneId::from(Zone::Esamir);
+        let indar = ZoneId::from(Zone::Indar);
+        let mut tracker = AlertTracker::new();
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Started, esamir, 100));
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Started, indar, 200));
+        assert_eq!(tracker.active(WorldId::new(10)).count(), 2);
+
+        tracker.handle_metagame_event(&metagame_event(MetagameEventState::Ended, indar, 5600));
+        let alert = tracker.active_in(WorldId::new(10), esamir).unwrap();
+        assert_eq!(alert.start, Timestamp::from_secs(100));
+        assert_eq!(tracker.active_in(WorldId::new(10), indar), None);
+
+        let notifications: Vec<_> = tracker.drain().collect();
+        assert_eq!(notifications.len(), 3);
+        match notifications[2] {
+            AlertNotification::Ended(ref ended) => {
+                assert_eq!(ended.zone_id, indar);
+                assert_eq!(ended.start, Some(Timestamp::from_secs(200)));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn territory_leader() {
+        let tie = Territory {
+            vs: 40.0,
+            nc: 40.0,
+            tr: 20.0,
+        };
+        assert_eq!(tie.leader(), None);
+        assert_eq!(Territory::default().leader(), None);
+    }
+}
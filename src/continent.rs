@@ -0,0 +1,293 @@
+//! Tracking of open and locked continents.
+
+use std::collections::HashMap;
+
+use faction::Faction;
+use handler::EventHandler;
+use response::{ContinentLock, ContinentUnlock, MetagameEvent, MetagameEventState};
+use zone::ZoneId;
+use {Timestamp, WorldId};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContinentState {
+    /// Open at least since the given time, i.e. since it was unlocked or an alert started on it.
+    Open { since: Timestamp },
+    /// Locked by the given faction, if known.
+    Locked {
+        faction: Option<Faction>,
+        since: Timestamp,
+    },
+}
+
+impl ContinentState {
+    pub fn is_open(&self) -> bool {
+        match *self {
+            ContinentState::Open { .. } => true,
+            ContinentState::Locked { .. } => false,
+        }
+    }
+
+    /// The time the continent has been in this state since.
+    pub fn since(&self) -> Timestamp {
+        match *self {
+            ContinentState::Open { since } | ContinentState::Locked { since, .. } => since,
+        }
+    }
+}
+
+/// A continent that opened or locked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ContinentChange {
+    pub world_id: WorldId,
+    pub zone_id: ZoneId,
+    /// The state before, unknown for continents not seen yet.
+    pub previous: Option<ContinentState>,
+    pub state: ContinentState,
+}
+
+/// Keeps the state of each world's continents.
+///
+/// Continents lock and unlock with `ContinentLock` and `ContinentUnlock`. Since alerts only run on
+/// open continents, a starting alert opens its continent as well, which covers unlocks missed
+/// while disconnected. Continents not seen in any of these events have an unknown state, and events
+/// older than a continent's current state are ignored.
+///
+/// Changes are kept until taken with `drain`.
+#[derive(Clone, Debug, Default)]
+pub struct ContinentTracker {
+    continents: HashMap<(WorldId, ZoneId), ContinentState>,
+    changes: Vec<ContinentChange>,
+}
+
+impl ContinentTracker {
+    pub fn new() -> ContinentTracker {
+        ContinentTracker::default()
+    }
+
+    pub fn handle_continent_lock(&mut self, event: &ContinentLock) {
+        self.change(
+            event.world_id,
+            event.zone_id,
+            ContinentState::Locked {
+                faction: event.triggering_faction(),
+                since: event.timestamp,
+            },
+        );
+    }
+
+    pub fn handle_continent_unlock(&mut self, event: &ContinentUnlock) {
+        self.change(
+            event.world_id,
+            event.zone_id,
+            ContinentState::Open {
+                since: event.timestamp,
+            },
+        );
+    }
+
+    pub fn handle_metagame_event(&mut self, event: &MetagameEvent) {
+        let starts = matches!(
+            event.state(),
+            Some(MetagameEventState::Started) | Some(MetagameEventState::Restarted)
+        );
+        let on_continent = !event.zone_id.is_instanced()
+            && event.zone_id.zone().map_or(true, |zone| zone.is_continent());
+        if !starts || !on_continent {
+            return;
+        }
+
+        let open = self.state(event.world_id, event.zone_id)
+            .map_or(false, |state| state.is_open());
+        if !open {
+            self.change(
+                event.world_id,
+                event.zone_id,
+                ContinentState::Open {
+                    since: event.timestamp,
+                },
+            );
+        }
+    }
+
+    fn change(&mut self, world_id: WorldId, zone_id: ZoneId, state: ContinentState) {
+        let stale = self.state(world_id, zone_id)
+            .map_or(false, |current| state.since() < current.since());
+        if stale {
+            return;
+        }
+
+        let previous = self.continents.insert((world_id, zone_id), state);
+        let changed = match (previous, state) {
+            (Some(ContinentState::Open { .. }), ContinentState::Open { .. }) => false,
+            (
+                Some(ContinentState::Locked { faction, .. }),
+                ContinentState::Locked {
+                    faction: new_faction,
+                    ..
+                },
+            ) => faction != new_faction,
+            _ => true,
+        };
+
+        if changed {
+            self.changes.push(ContinentChange {
+                world_id,
+                zone_id,
+                previous,
+                state,
+            });
+        } else if let Some(previous) = previous {
+            // Keep the earlier time the continent has been in this state since.
+            self.continents.insert((world_id, zone_id), previous);
+        }
+    }
+
+    /// The state of a continent on a world, if known.
+    pub fn state(&self, world_id: WorldId, zone_id: ZoneId) -> Option<&ContinentState> {
+        self.continents.get(&(world_id, zone_id))
+    }
+
+    /// The continents of a world known to be open, ordered by zone id.
+    pub fn open_continents(&self, world_id: WorldId) -> Vec<ZoneId> {
+        let mut open: Vec<_> = self.continents
+            .iter()
+            .filter(|&(&(world, _), state)| world == world_id && state.is_open())
+            .map(|(&(_, zone_id), _)| zone_id)
+            .collect();
+        open.sort();
+
+        open
+    }
+
+    /// The continents of a world known to be locked, ordered by zone id.
+    pub fn locked_continents(&self, world_id: WorldId) -> Vec<(ZoneId, ContinentState)> {
+        let mut locked: Vec<_> = self.continents
+            .iter()
+            .filter(|&(&(world, _), state)| world == world_id && !state.is_open())
+            .map(|(&(_, zone_id), &state)| (zone_id, state))
+            .collect();
+        locked.sort_by_key(|&(zone_id, _)| zone_id);
+
+        locked
+    }
+
+    /// Takes the changes so far, oldest first.
+    pub fn drain(&mut self) -> ::std::vec::Drain<'_, ContinentChange> {
+        self.changes.drain(..)
+    }
+}
+
+impl EventHandler for ContinentTracker {
+    fn on_continent_lock(&mut self, event: &ContinentLock) {
+        self.handle_continent_lock(event)
+    }
+
+    fn on_continent_unlock(&mut self, event: &ContinentUnlock) {
+        self.handle_continent_unlock(event)
+    }
+
+    fn on_metagame_event(&mut self, event: &MetagameEvent) {
+        self.handle_metagame_event(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::metagame_event;
+    use zone::Zone;
+    use MetagameEventId;
+
+    fn continent_lock(zone: Zone, triggering_faction: u64, timestamp: u64) -> ContinentLock {
+        ContinentLock {
+            metagame_event_id: MetagameEventId::new(147),
+            nc_population: 30,
+            previous_faction: 0,
+            timestamp: Timestamp::from_secs(timestamp),
+            tr_population: 30,
+            triggering_faction,
+            vs_population: 40,
+            world_id: WorldId::new(10),
+            zone_id: ZoneId::from(zone),
+        }
+    }
+
+    fn continent_unlock(zone: Zone, timestamp: u64) -> ContinentUnlock {
+        ContinentUnlock {
+            metagame_event_id: MetagameEventId::new(0),
+            nc_population: 0,
+            previous_faction: 0,
+            timestamp: Timestamp::from_secs(timestamp),
+            tr_population: 0,
+            triggering_faction: 0,
+            vs_population: 0,
+            world_id: WorldId::new(10),
+            zone_id: ZoneId::from(zone),
+        }
+    }
+
+    #[test]
+    fn lock_unlock() {
+        let mut tracker = ContinentTracker::new();
+        tracker.handle_continent_unlock(&continent_unlock(Zone::Indar, 100));
+        tracker.handle_continent_unlock(&continent_unlock(Zone::Esamir, 100));
+        tracker.handle_continent_unlock(&continent_unlock(Zone::Indar, 200));
+        tracker.handle_continent_lock(&continent_lock(Zone::Esamir, 1, 300));
+
+        let world_id = WorldId::new(10);
+        assert_eq!(tracker.open_continents(world_id), vec![ZoneId::from(Zone::Indar)]);
+        assert_eq!(
+            tracker.state(world_id, ZoneId::from(Zone::Indar)),
+            Some(&ContinentState::Open {
+                since: Timestamp::from_secs(100),
+            })
+        );
+        assert_eq!(
+            tracker.locked_continents(world_id),
+            vec![(
+                ZoneId::from(Zone::Esamir),
+                ContinentState::Locked {
+                    faction: Some(Faction::Vs),
+                    since: Timestamp::from_secs(300),
+                },
+            )]
+        );
+        assert_eq!(tracker.drain().count(), 3);
+    }
+
+    #[test]
+    fn alert_opens() {
+        let started = MetagameEventState::Started;
+        let mut tracker = ContinentTracker::new();
+        tracker.handle_continent_lock(&continent_lock(Zone::Amerish, 2, 100));
+        tracker.handle_metagame_event(&metagame_event(started, ZoneId::from(Zone::Amerish), 200));
+        tracker.handle_metagame_event(&metagame_event(started, ZoneId::from_parts(361, 3), 200));
+
+        let changes: Vec<_> = tracker.drain().collect();
+        assert_eq!(changes.len(), 2);
+        assert!(changes[1].previous.map_or(false, |state| !state.is_open()));
+        assert!(changes[1].state.is_open());
+        assert_eq!(
+            tracker.open_continents(WorldId::new(10)),
+            vec![ZoneId::from(Zone::Amerish)]
+        );
+    }
+
+    #[test]
+    fn late_events() {
+        let started = MetagameEventState::Started;
+        let mut tracker = ContinentTracker::new();
+        tracker.handle_continent_lock(&continent_lock(Zone::Hossin, 3, 300));
+        tracker.handle_continent_unlock(&continent_unlock(Zone::Hossin, 200));
+        tracker.handle_metagame_event(&metagame_event(started, ZoneId::from(Zone::Hossin), 250));
+
+        assert_eq!(tracker.drain().count(), 1);
+        assert_eq!(
+            tracker.state(WorldId::new(10), ZoneId::from(Zone::Hossin)),
+            Some(&ContinentState::Locked {
+                faction: Some(Faction::Tr),
+                since: Timestamp::from_secs(300),
+            })
+        );
+    }
+}
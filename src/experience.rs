@@ -0,0 +1,237 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use request::EventNames;
+use ExperienceId;
+
+/// The commonly used `experience_id`s.
+///
+/// This is not the complete catalog, e.g. the per-vehicle kill and repair ids are missing.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[repr(u64)]
+pub enum Experience {
+    Kill = 1,
+    KillAssist = 2,
+    KillSpawnAssist = 3,
+    Heal = 4,
+    HealAssist = 5,
+    MaxRepair = 6,
+    Revive = 7,
+    KillStreak = 8,
+    DominationKill = 10,
+    RevengeKill = 11,
+    ControlPointDefend = 15,
+    ControlPointAttack = 16,
+    MultipleKill = 25,
+    MaxKill = 29,
+    TransportAssist = 30,
+    Resupply = 34,
+    SpotKill = 36,
+    Headshot = 37,
+    StopKillStreak = 38,
+    SquadHeal = 51,
+    SquadRevive = 53,
+    SquadSpotKill = 54,
+    SquadResupply = 55,
+    SquadSpawn = 56,
+    SquadMaxRepair = 142,
+    GalaxySpawnBonus = 201,
+    MotionDetect = 293,
+    SquadMotionSpot = 294,
+    ShieldRepair = 438,
+    SquadShieldRepair = 439,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum ExperienceCategory {
+    /// Kills and everything that rewards them.
+    Combat,
+    /// Keeping others alive: heals, revives and repairs.
+    Support,
+    /// Getting others and their equipment into the fight.
+    Logistics,
+    /// Spotting and detecting enemies.
+    Recon,
+    /// Fighting over control points.
+    Objective,
+}
+
+impl Experience {
+    pub const ALL: [Experience; 30] = [
+        Experience::Kill,
+        Experience::KillAssist,
+        Experience::KillSpawnAssist,
+        Experience::Heal,
+        Experience::HealAssist,
+        Experience::MaxRepair,
+        Experience::Revive,
+        Experience::KillStreak,
+        Experience::DominationKill,
+        Experience::RevengeKill,
+        Experience::ControlPointDefend,
+        Experience::ControlPointAttack,
+        Experience::MultipleKill,
+        Experience::MaxKill,
+        Experience::TransportAssist,
+        Experience::Resupply,
+        Experience::SpotKill,
+        Experience::Headshot,
+        Experience::StopKillStreak,
+        Experience::SquadHeal,
+        Experience::SquadRevive,
+        Experience::SquadSpotKill,
+        Experience::SquadResupply,
+        Experience::SquadSpawn,
+        Experience::SquadMaxRepair,
+        Experience::GalaxySpawnBonus,
+        Experience::MotionDetect,
+        Experience::SquadMotionSpot,
+        Experience::ShieldRepair,
+        Experience::SquadShieldRepair,
+    ];
+
+    pub fn id(self) -> ExperienceId {
+        ExperienceId::new(self as u64)
+    }
+
+    /// The description as listed in the Census `experience` collection.
+    pub fn description(self) -> &'static str {
+        use self::Experience::*;
+
+        match self {
+            Kill => "Kill Player",
+            KillAssist => "Kill Player Assist",
+            KillSpawnAssist => "Kill Player Spawn Assist",
+            Heal => "Heal Player",
+            HealAssist => "Heal Assist",
+            MaxRepair => "MAX Repair",
+            Revive => "Revive",
+            KillStreak => "Kill Streak",
+            DominationKill => "Domination Kill",
+            RevengeKill => "Revenge Kill",
+            ControlPointDefend => "Control Point - Defend",
+            ControlPointAttack => "Control Point - Attack",
+            MultipleKill => "Multiple Kill",
+            MaxKill => "Kill MAX",
+            TransportAssist => "Transport Assist",
+            Resupply => "Resupply Player",
+            SpotKill => "Spot Kill",
+            Headshot => "Headshot",
+            StopKillStreak => "Stop Kill Streak",
+            SquadHeal => "Squad Heal",
+            SquadRevive => "Squad Revive",
+            SquadSpotKill => "Squad Spot Kill",
+            SquadResupply => "Squad Resupply",
+            SquadSpawn => "Squad Spawn",
+            SquadMaxRepair => "Squad MAX Repair",
+            GalaxySpawnBonus => "Galaxy Spawn Bonus",
+            MotionDetect => "Motion Detect",
+            SquadMotionSpot => "Squad Motion Spot",
+            ShieldRepair => "Shield Repair",
+            SquadShieldRepair => "Squad Shield Repair",
+        }
+    }
+
+    pub fn category(self) -> ExperienceCategory {
+        use self::Experience::*;
+        use self::ExperienceCategory::*;
+
+        match self {
+            Kill | KillAssist | KillSpawnAssist | KillStreak | DominationKill | RevengeKill
+            | MultipleKill | MaxKill | Headshot | StopKillStreak => Combat,
+            Heal | HealAssist | MaxRepair | Revive | SquadHeal | SquadRevive | SquadMaxRepair
+            | ShieldRepair | SquadShieldRepair => Support,
+            TransportAssist | Resupply | SquadResupply | SquadSpawn | GalaxySpawnBonus => {
+                Logistics
+            }
+            SpotKill | SquadSpotKill | MotionDetect | SquadMotionSpot => Recon,
+            ControlPointDefend | ControlPointAttack => Objective,
+        }
+    }
+
+    /// All known experiences of a category, e.g. to subscribe to them.
+    pub fn of_category(category: ExperienceCategory) -> impl Iterator<Item = Experience> {
+        Experience::ALL
+            .iter()
+            .cloned()
+            .filter(move |experience| experience.category() == category)
+    }
+}
+
+impl From<Experience> for ExperienceId {
+    fn from(experience: Experience) -> ExperienceId {
+        experience.id()
+    }
+}
+
+impl From<Experience> for EventNames {
+    fn from(experience: Experience) -> EventNames {
+        EventNames::GainExperienceId(experience.id())
+    }
+}
+
+impl TryFrom<ExperienceId> for Experience {
+    type Error = TryFromExperienceIdError;
+
+    fn try_from(id: ExperienceId) -> Result<Experience, TryFromExperienceIdError> {
+        Experience::ALL
+            .iter()
+            .cloned()
+            .find(|experience| experience.id() == id)
+            .ok_or(TryFromExperienceIdError(id))
+    }
+}
+
+impl fmt::Display for Experience {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromExperienceIdError(ExperienceId);
+
+impl fmt::Display for TryFromExperienceIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown experience id {}", self.0)
+    }
+}
+
+impl ::std::error::Error for TryFromExperienceIdError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_experience_id() {
+        assert_eq!(Experience::try_from(ExperienceId::new(7)), Ok(Experience::Revive));
+        assert_eq!(Experience::try_from(ExperienceId::new(56)), Ok(Experience::SquadSpawn));
+        assert!(Experience::try_from(ExperienceId::new(9)).is_err());
+
+        for experience in Experience::ALL.iter() {
+            assert_eq!(Experience::try_from(experience.id()), Ok(*experience));
+        }
+    }
+
+    #[test]
+    fn category() {
+        assert_eq!(Experience::Headshot.category(), ExperienceCategory::Combat);
+        assert_eq!(Experience::SquadRevive.category(), ExperienceCategory::Support);
+        assert_eq!(Experience::SquadSpawn.category(), ExperienceCategory::Logistics);
+
+        let objective: Vec<_> = Experience::of_category(ExperienceCategory::Objective).collect();
+        assert_eq!(
+            objective,
+            vec![Experience::ControlPointDefend, Experience::ControlPointAttack]
+        );
+    }
+
+    #[test]
+    fn event_names() {
+        assert_eq!(
+            EventNames::from(Experience::Revive),
+            EventNames::GainExperienceId(ExperienceId::new(7))
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! Tracking of facility ownership from `FacilityControl` events, and of the characters taking
+//! part in captures.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use faction::Faction;
+use handler::EventHandler;
+use response::{FacilityControl, PlayerFacilityCapture};
+use zone::ZoneId;
+use {CharacterId, FacilityId, Id, OutfitId, Timestamp, WorldId};
+
+/// Who owns a facility, and since when.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Ownership {
+    pub faction_id: Id,
+    /// The outfit that captured the facility, `0` if none or unknown.
+    pub outfit_id: OutfitId,
+    /// When the facility was captured.
+    pub since: Timestamp,
+    /// When the ownership was last confirmed by an event.
+    pub last_seen: Timestamp,
+}
+
+impl Ownership {
+    pub fn faction(&self) -> Option<Faction> {
+        Faction::try_from(self.faction_id).ok()
+    }
+
+    pub fn outfit_id(&self) -> Option<OutfitId> {
+        if self.outfit_id.get() == 0 {
+            None
+        } else {
+            Some(self.outfit_id)
+        }
+    }
+
+    /// How long the facility has been held as of `now`.
+    pub fn held_for(&self, now: Timestamp) -> Duration {
+        now.saturating_duration_since(self.since)
+    }
+}
+
+/// The persisted form of one facility of a `FacilityMap`.
+#[derive(Serialize, Deserialize)]
+struct FacilityRecord {
+    world_id: WorldId,
+    zone_id: ZoneId,
+    facility_id: FacilityId,
+    #[serde(flatten)] ownership: Ownership,
+}
+
+/// The owners of all facilities seen, per world and zone instance.
+///
+/// Captures update the owner and outfit. Defenses only confirm the owner, but tell when the
+/// facility was captured from their `duration_held`, so a map restored from an older snapshot or
+/// started mid-fight learns the capture times as facilities are defended.
+///
+/// Serializes as a list of facilities, to persist and restore snapshots.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(from = "Vec<FacilityRecord>", into = "Vec<FacilityRecord>")]
+pub struct FacilityMap {
+    facilities: HashMap<(WorldId, ZoneId, FacilityId), Ownership>,
+}
+
+impl FacilityMap {
+    pub fn new() -> FacilityMap {
+        FacilityMap::default()
+    }
+
+    pub fn handle_facility_control(&mut self, event: &FacilityControl) {
+        let key = (event.world_id, event.zone_id, event.facility_id);
+        if self.facilities
+            .get(&key)
+            .map_or(false, |ownership| ownership.last_seen > event.timestamp)
+        {
+            return;
+        }
+
+        let captured = event.new_faction_id != event.old_faction_id;
+        let previous = self.facilities.get(&key).cloned();
+        let ownership = if captured {
+            Ownership {
+                faction_id: event.new_faction_id,
+                outfit_id: event.outfit_id,
+                since: event.timestamp,
+                last_seen: event.timestamp,
+            }
+        } else {
+            let since = event
+                .timestamp
+                .checked_sub(Duration::from_secs(event.duration_held))
+                .unwrap_or(Timestamp::UNIX_EPOCH);
+            let outfit_id = match previous {
+                Some(previous) if previous.faction_id == event.new_faction_id => previous.outfit_id,
+                _ => OutfitId::new(0),
+            };
+
+            Ownership {
+                faction_id: event.new_faction_id,
+                outfit_id,
+                since,
+                last_seen: event.timestamp,
+            }
+        };
+
+        self.facilities.insert(key, ownership);
+    }
+
+    pub fn owner(
+        &self,
+        world_id: WorldId,
+        zone_id: ZoneId,
+        facility_id: FacilityId,
+    ) -> Option<&Ownership> {
+        self.facilities.get(&(world_id, zone_id, facility_id))
+    }
+
+    /// The facilities of a zone instance on a world, ordered by facility id.
+    pub fn zone(&self, world_id: WorldId, zone_id: ZoneId) -> Vec<(FacilityId, Ownership)> {
+        let mut facilities: Vec<_> = self.facilities
+            .iter()
+            .filter(|&(&(world, zone, _), _)| world == world_id && zone == zone_id)
+            .map(|(&(_, _, facility_id), &ownership)| (facility_id, ownership))
+            .collect();
+        facilities.sort_by_key(|&(facility_id, _)| facility_id);
+
+        facilities
+    }
+
+    /// All facilities with their world and zone instance.
+    pub fn iter(&self) -> impl Iterator<Item = (WorldId, ZoneId, FacilityId, &Ownership)> {
+        self.facilities
+            .iter()
+            .map(|(&(world_id, zone_id, facility_id), ownership)| {
+                (world_id, zone_id, facility_id, ownership)
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.facilities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.facilities.is_empty()
+    }
+
+    /// Forgets the facilities of a zone instance, e.g. after its continent locked or the
+    /// instance closed.
+    pub fn clear_zone(&mut self, world_id: WorldId, zone_id: ZoneId) {
+        self.facilities
+            .retain(|&(world, zone, _), _| world != world_id || zone != zone_id);
+    }
+}
+
+impl EventHandler for FacilityMap {
+    fn on_facility_control(&mut self, event: &FacilityControl) {
+        self.handle_facility_control(event)
+    }
+}
+
+impl From<Vec<FacilityRecord>> for FacilityMap {
+    fn from(records: Vec<FacilityRecord>) -> FacilityMap {
+        let facilities = records
+            .into_iter()
+            .map(|record| {
+                (
+                    (record.world_id, record.zone_id, record.facility_id),
+                    record.ownership,
+                )
+            })
+            .collect();
+
+        FacilityMap { facilities }
+    }
+}
+
+impl From<FacilityMap> for Vec<FacilityRecord> {
+    fn from(map: FacilityMap) -> Vec<FacilityRecord> {
+        let mut records: Vec<_> = map.facilities
+            .into_iter()
+            .map(|((world_id, zone_id, facility_id), ownership)| FacilityRecord {
+                world_id,
+                zone_id,
+                facility_id,
+                ownership,
+            })
+            .collect();
+        records.sort_by_key(|record| (record.world_id, record.zone_id, record.facility_id));
+
+        records
+    }
+}
+
+/// A facility flip along with the characters that took part in it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Capture {
+    pub facility_id: FacilityId,
+    pub zone_id: ZoneId,
+    pub world_id: WorldId,
+    pub new_faction_id: Id,
+    pub old_faction_id: Id,
+    /// The outfit that captured the facility, `0` if none.
+    pub outfit_id: OutfitId,
+    pub timestamp: Timestamp,
+    /// The characters of each `PlayerFacilityCapture`, in order of arrival.
+    pub participants: Vec<CharacterId>,
+}
+
+impl Capture {
+    pub fn new_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.new_faction_id).ok()
+    }
+
+    pub fn old_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.old_faction_id).ok()
+    }
+}
+
+type FacilityKey = (WorldId, ZoneId, FacilityId);
+
+/// Links the `PlayerFacilityCapture` events of a flip to its `FacilityControl` event.
+///
+/// The events of one flip arrive within moments of each other, in either order. Player captures
+/// within the window before or after a flip of the same facility on the same world and zone
+/// instance are its participants. A `Capture` is complete once the latest event seen is more than
+/// the window past its flip, and is then kept until taken with `drain`. Defenses and repeated
+/// deliveries of a pending flip are ignored.
+#[derive(Clone, Debug)]
+pub struct CaptureCorrelator {
+    window: Duration,
+    pending: HashMap<FacilityKey, Capture>,
+    unmatched: Vec<PlayerFacilityCapture>,
+    completed: Vec<Capture>,
+    latest: Option<Timestamp>,
+}
+
+impl CaptureCorrelator {
+    pub fn new(window: Duration) -> CaptureCorrelator {
+        CaptureCorrelator {
+            window,
+            pending: HashMap::new(),
+            unmatched: Vec::new(),
+            completed: Vec::new(),
+            latest: None,
+        }
+    }
+
+    pub fn handle_facility_control(&mut self, event: &FacilityControl) {
+        self.advance(event.timestamp);
+        if event.new_faction_id == event.old_faction_id {
+            return;
+        }
+
+        let key = (event.world_id, event.zone_id, event.facility_id);
+        // The same flip may be delivered again, e.g. after resubscribing.
+        let duplicate = self.pending.get(&key).map_or(false, |pending| {
+            pending.timestamp == event.timestamp
+                && pending.new_faction_id == event.new_faction_id
+                && pending.old_faction_id == event.old_faction_id
+        });
+        if duplicate {
+            return;
+        }
+
+        let window = self.window;
+        let mut capture = Capture {
+            facility_id: event.facility_id,
+            zone_id: event.zone_id,
+            world_id: event.world_id,
+            new_faction_id: event.new_faction_id,
+            old_faction_id: event.old_faction_id,
+            outfit_id: event.outfit_id,
+            timestamp: event.timestamp,
+            participants: Vec::new(),
+        };
+        self.unmatched.retain(|player| {
+            let matches = (player.world_id, player.zone_id, player.facility_id) == key
+                && within(player.timestamp, capture.timestamp, window);
+            if matches {
+                capture.participants.push(player.character_id);
+            }
+
+            !matches
+        });
+
+        if let Some(previous) = self.pending.insert(key, capture) {
+            self.completed.push(previous);
+            self.completed.sort_by_key(|capture| capture.timestamp);
+        }
+    }
+
+    pub fn handle_player_facility_capture(&mut self, event: &PlayerFacilityCapture) {
+        self.advance(event.timestamp);
+
+        let key = (event.world_id, event.zone_id, event.facility_id);
+        match self.pending.get_mut(&key) {
+            Some(capture) if within(event.timestamp, capture.timestamp, self.window) => {
+                capture.participants.push(event.character_id);
+            }
+            _ => self.unmatched.push(event.clone()),
+        }
+    }
+
+    /// Completes the captures and forgets the player captures that are more than the window
+    /// before `now`.
+    fn advance(&mut self, now: Timestamp) {
+        if self.latest.map_or(false, |latest| latest >= now) {
+            return;
+        }
+        self.latest = Some(now);
+
+        let window = self.window;
+        let expired = |timestamp: Timestamp| now.saturating_duration_since(timestamp) > window;
+
+        let completed: Vec<_> = self.pending
+            .iter()
+            .filter(|&(_, capture)| expired(capture.timestamp))
+            .map(|(&key, _)| key)
+            .collect();
+        for key in completed {
+            if let Some(capture) = self.pending.remove(&key) {
+                self.completed.push(capture);
+            }
+        }
+        self.completed.sort_by_key(|capture| capture.timestamp);
+
+        self.unmatched.retain(|player| !expired(player.timestamp));
+    }
+
+    /// Completes all pending captures, e.g. at the end of a replay.
+    pub fn finish(&mut self) {
+        self.completed.extend(self.pending.drain().map(|(_, capture)| capture));
+        self.completed.sort_by_key(|capture| capture.timestamp);
+        self.unmatched.clear();
+    }
+
+    /// Takes the completed captures, oldest first.
+    pub fn drain(&mut self) -> ::std::vec::Drain<'_, Capture> {
+        self.completed.drain(..)
+    }
+}
+
+impl EventHandler for CaptureCorrelator {
+    fn on_facility_control(&mut self, event: &FacilityControl) {
+        self.handle_facility_control(event)
+    }
+
+    fn on_player_facility_capture(&mut self, event: &PlayerFacilityCapture) {
+        self.handle_player_facility_capture(event)
+    }
+}
+
+fn within(a: Timestamp, b: Timestamp, window: Duration) -> bool {
+    a.saturating_duration_since(b).max(b.saturating_duration_since(a)) <= window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::{facility_control, player_capture};
+    use serde_json;
+    use zone::Zone;
+
+    #[test]
+    fn captures_and_defenses() {
+        let mut map = FacilityMap::new();
+        map.handle_facility_control(&facility_control(222280, 3, 2, 37509488620604883, 600, 1000));
+        map.handle_facility_control(&facility_control(222280, 2, 2, 37511594860086180, 300, 1300));
+        map.handle_facility_control(&facility_control(222290, 1, 1, 0, 3600, 5000));
+
+        let world_id = WorldId::new(1);
+        let zone_id = ZoneId::from(Zone::Oshur);
+        let captured = map.owner(world_id, zone_id, FacilityId::new(222280)).unwrap();
+        assert_eq!(captured.faction(), Some(Faction::Nc));
+        assert_eq!(captured.outfit_id(), Some(OutfitId::new(37509488620604883)));
+        assert_eq!(captured.since, Timestamp::from_secs(1000));
+        assert_eq!(
+            captured.held_for(Timestamp::from_secs(1600)),
+            Duration::from_secs(600)
+        );
+
+        let defended = map.owner(world_id, zone_id, FacilityId::new(222290)).unwrap();
+        assert_eq!(defended.faction(), Some(Faction::Vs));
+        assert_eq!(defended.outfit_id(), None);
+        assert_eq!(defended.since, Timestamp::from_secs(1400));
+
+        assert_eq!(map.zone(world_id, zone_id).len(), 2);
+        map.clear_zone(world_id, zone_id);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn persist_restore() {
+        let mut map = FacilityMap::new();
+        map.handle_facility_control(&facility_control(222280, 3, 2, 37509488620604883, 600, 1000));
+        map.handle_facility_control(&facility_control(222290, 1, 1, 0, 3600, 5000));
+
+        let snapshot = serde_json::to_value(&map).unwrap();
+        assert_eq!(
+            snapshot[0],
+            json!({
+                "world_id": "1",
+                "zone_id": "344",
+                "facility_id": "222280",
+                "faction_id": 2,
+                "outfit_id": "37509488620604883",
+                "since": "1000",
+                "last_seen": "1000"
+            })
+        );
+
+        let restored: FacilityMap = serde_json::from_value(snapshot).unwrap();
+        assert_eq!(restored, map);
+    }
+
+    #[test]
+    fn correlate_captures() {
+        let mut correlator = CaptureCorrelator::new(Duration::from_secs(5));
+        correlator.handle_player_facility_capture(&player_capture(1, 222280, 0, 998));
+        correlator.handle_player_facility_capture(&player_capture(2, 222280, 0, 990));
+        correlator.handle_facility_control(&facility_control(222280, 3, 2, 10, 600, 1000));
+        correlator.handle_player_facility_capture(&player_capture(3, 222280, 0, 1001));
+        correlator.handle_player_facility_capture(&player_capture(4, 222290, 0, 1001));
+        correlator.handle_facility_control(&facility_control(222290, 1, 1, 0, 600, 1002));
+        assert_eq!(correlator.drain().count(), 0);
+
+        correlator.handle_player_facility_capture(&player_capture(5, 222280, 0, 1010));
+
+        let captures: Vec<_> = correlator.drain().collect();
+        assert_eq!(
+            captures,
+            vec![Capture {
+                facility_id: FacilityId::new(222280),
+                zone_id: ZoneId::from(Zone::Oshur),
+                world_id: WorldId::new(1),
+                new_faction_id: 2,
+                old_faction_id: 3,
+                outfit_id: OutfitId::new(10),
+                timestamp: Timestamp::from_secs(1000),
+                participants: vec![CharacterId::new(1), CharacterId::new(3)],
+            }]
+        );
+        assert_eq!(captures[0].new_faction(), Some(Faction::Nc));
+
+        correlator.handle_facility_control(&facility_control(222280, 2, 3, 0, 10, 1012));
+        correlator.finish();
+        let captures: Vec<_> = correlator.drain().collect();
+        assert_eq!(captures.len(), 1);
+        assert_eq!(captures[0].participants, vec![CharacterId::new(5)]);
+    }
+
+    #[test]
+    fn correlate_late_flips() {
+        let mut correlator = CaptureCorrelator::new(Duration::from_secs(60));
+        correlator.handle_facility_control(&facility_control(222280, 3, 2, 0, 600, 240));
+        correlator.handle_player_facility_capture(&player_capture(1, 222290, 0, 305));
+        correlator.handle_facility_control(&facility_control(222290, 3, 1, 0, 600, 100));
+        correlator.handle_facility_control(&facility_control(222290, 1, 2, 0, 20, 120));
+
+        let captures: Vec<_> = correlator.drain().collect();
+        assert_eq!(
+            captures.iter().map(|capture| capture.timestamp.as_secs()).collect::<Vec<_>>(),
+            vec![100, 240]
+        );
+    }
+
+    #[test]
+    fn correlate_duplicate_flips() {
+        let mut correlator = CaptureCorrelator::new(Duration::from_secs(5));
+        correlator.handle_facility_control(&facility_control(222280, 3, 2, 10, 600, 1000));
+        correlator.handle_player_facility_capture(&player_capture(1, 222280, 10, 1001));
+        correlator.handle_facility_control(&facility_control(222280, 3, 2, 10, 600, 1000));
+        correlator.finish();
+
+        let captures: Vec<_> = correlator.drain().collect();
+        assert_eq!(captures.len(), 1);
+        assert_eq!(captures[0].participants, vec![CharacterId::new(1)]);
+    }
+}
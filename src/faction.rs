@@ -0,0 +1,122 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use Id;
+
+/// The playable factions.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[repr(u64)]
+pub enum Faction {
+    /// Vanu Sovereignty
+    Vs = 1,
+    /// New Conglomerate
+    Nc = 2,
+    /// Terran Republic
+    Tr = 3,
+    /// Nanite Systems Operatives, who fight for whichever empire they are assigned to.
+    Nso = 4,
+}
+
+impl Faction {
+    pub const ALL: [Faction; 4] = [Faction::Vs, Faction::Nc, Faction::Tr, Faction::Nso];
+
+    pub fn id(self) -> Id {
+        self as Id
+    }
+
+    pub fn tag(self) -> &'static str {
+        use self::Faction::*;
+
+        match self {
+            Vs => "VS",
+            Nc => "NC",
+            Tr => "TR",
+            Nso => "NSO",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        use self::Faction::*;
+
+        match self {
+            Vs => "Vanu Sovereignty",
+            Nc => "New Conglomerate",
+            Tr => "Terran Republic",
+            Nso => "Nanite Systems Operatives",
+        }
+    }
+}
+
+impl TryFrom<Id> for Faction {
+    type Error = TryFromFactionIdError;
+
+    /// Fails for unknown ids as well as for `0`, which events use for "no faction".
+    fn try_from(id: Id) -> Result<Faction, TryFromFactionIdError> {
+        Faction::ALL
+            .iter()
+            .cloned()
+            .find(|faction| faction.id() == id)
+            .ok_or(TryFromFactionIdError(id))
+    }
+}
+
+impl fmt::Display for Faction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+impl FromStr for Faction {
+    type Err = ParseFactionError;
+
+    /// Parses a faction by its case-insensitive tag, e.g. `"VS"`.
+    fn from_str(s: &str) -> Result<Faction, ParseFactionError> {
+        Faction::ALL
+            .iter()
+            .cloned()
+            .find(|faction| faction.tag().eq_ignore_ascii_case(s))
+            .ok_or(ParseFactionError(()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromFactionIdError(Id);
+
+impl fmt::Display for TryFromFactionIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown faction id {}", self.0)
+    }
+}
+
+impl ::std::error::Error for TryFromFactionIdError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFactionError(());
+
+impl fmt::Display for ParseFactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unknown faction tag")
+    }
+}
+
+impl ::std::error::Error for ParseFactionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_faction_id() {
+        assert_eq!(Faction::try_from(1), Ok(Faction::Vs));
+        assert_eq!(Faction::try_from(4), Ok(Faction::Nso));
+        assert!(Faction::try_from(0).is_err());
+    }
+
+    #[test]
+    fn fromstr_display() {
+        assert_eq!("nc".parse(), Ok(Faction::Nc));
+        assert_eq!(Faction::Tr.to_string(), "TR");
+        assert!("NS".parse::<Faction>().is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Event builders shared by the tests of the trackers.
+
+use response::{Event, FacilityControl, MetagameEvent, MetagameEventState, PlayerFacilityCapture,
+               PlayerLogin, PlayerLogout, SkillAdded};
+use zone::{Zone, ZoneId};
+use {CharacterId, FacilityId, Id, MetagameEventId, OutfitId, SkillId, Timestamp, WorldId};
+
+pub fn login(character_id: u64, world_id: u64, timestamp: u64) -> Event {
+    Event::PlayerLogin(PlayerLogin {
+        character_id: CharacterId::new(character_id),
+        timestamp: Timestamp::from_secs(timestamp),
+        world_id: WorldId::new(world_id),
+    })
+}
+
+pub fn logout(character_id: u64, world_id: u64, timestamp: u64) -> Event {
+    Event::PlayerLogout(PlayerLogout {
+        character_id: CharacterId::new(character_id),
+        timestamp: Timestamp::from_secs(timestamp),
+        world_id: WorldId::new(world_id),
+    })
+}
+
+pub fn skill_added(character_id: u64, world_id: u64, timestamp: u64) -> Event {
+    Event::SkillAdded(SkillAdded {
+        character_id: CharacterId::new(character_id),
+        skill_id: SkillId::new(1),
+        timestamp: Timestamp::from_secs(timestamp),
+        world_id: WorldId::new(world_id),
+        zone_id: ZoneId::new(2),
+    })
+}
+
+/// A facility on Oshur of world 1 changing hands, or being defended for the same faction.
+pub fn facility_control(
+    facility_id: u64,
+    old_faction_id: Id,
+    new_faction_id: Id,
+    outfit_id: u64,
+    duration_held: u64,
+    timestamp: u64,
+) -> FacilityControl {
+    FacilityControl {
+        duration_held,
+        facility_id: FacilityId::new(facility_id),
+        new_faction_id,
+        old_faction_id,
+        outfit_id: OutfitId::new(outfit_id),
+        timestamp: Timestamp::from_secs(timestamp),
+        world_id: WorldId::new(1),
+        zone_id: ZoneId::from(Zone::Oshur),
+    }
+}
+
+/// A character taking part in a capture on Oshur of world 1.
+pub fn player_capture(
+    character_id: u64,
+    facility_id: u64,
+    outfit_id: u64,
+    timestamp: u64,
+) -> PlayerFacilityCapture {
+    PlayerFacilityCapture {
+        character_id: CharacterId::new(character_id),
+        facility_id: FacilityId::new(facility_id),
+        outfit_id: OutfitId::new(outfit_id),
+        timestamp: Timestamp::from_secs(timestamp),
+        world_id: WorldId::new(1),
+        zone_id: ZoneId::from(Zone::Oshur),
+    }
+}
+
+/// A change of alert 24789 on world 10, which the VS lead.
+pub fn metagame_event(
+    state: MetagameEventState,
+    zone_id: ZoneId,
+    timestamp: u64,
+) -> MetagameEvent {
+    MetagameEvent {
+        experience_bonus: 25.0,
+        faction_nc: 33.725491,
+        faction_tr: 25.490196,
+        faction_vs: 40.784313,
+        instance_id: 24789,
+        metagame_event_id: MetagameEventId::new(147),
+        metagame_event_state: state.id(),
+        metagame_event_state_name: state.name().to_string(),
+        timestamp: Timestamp::from_secs(timestamp),
+        world_id: WorldId::new(10),
+        zone_id,
+    }
+}
@@ -0,0 +1,428 @@
+//! Callback-style handling of push messages.
+//!
+//! Implement `EventHandler` for the events you care about and pass messages to `on_message`,
+//! which routes them to the matching callbacks. Tuples, `Vec`s and boxes of handlers are
+//! handlers themselves, so several can be attached to one stream:
+//!
+//! ```
+//! use planetside2_push::handler::EventHandler;
+//! use planetside2_push::response::{Death, PlayerLogin};
+//!
+//! #[derive(Default)]
+//! struct Kills(u64);
+//!
+//! impl EventHandler for Kills {
+//!     fn on_death(&mut self, _death: &Death) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! #[derive(Default)]
+//! struct Logins(u64);
+//!
+//! impl EventHandler for Logins {
+//!     fn on_player_login(&mut self, _login: &PlayerLogin) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let mut handlers = (Kills::default(), Logins::default());
+//! # let messages: Vec<planetside2_push::response::Message> = Vec::new();
+//! for message in &messages {
+//!     handlers.on_message(message);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use response::{AchievementEarned, BattleRankUp, ContinentLock, ContinentUnlock, Death, Event,
+               FacilityControl, GainExperience, ItemAdded, Message, MetagameEvent,
+               PlayerFacilityCapture, PlayerFacilityDefend, PlayerLogin, PlayerLogout,
+               SkillAdded, Subscription, VehicleDestroy};
+use CharacterId;
+
+/// Receives push messages, one callback per kind of message and event.
+///
+/// All callbacks do nothing by default.
+pub trait EventHandler {
+    /// Entry point for each received message.
+    ///
+    /// By default routes the message to the other callbacks with `dispatch`. Composite handlers
+    /// override this to forward the whole message to each of their parts.
+    fn on_message(&mut self, message: &Message) {
+        dispatch(self, message)
+    }
+
+    /// Called for the payload of each `Message::ServiceMessage`.
+    ///
+    /// By default routes the event to the per-event callbacks with `dispatch_event`.
+    fn on_event(&mut self, event: &Event) {
+        dispatch_event(self, event)
+    }
+
+    fn on_connection_state_changed(&mut self, _connected: bool) {}
+
+    fn on_heartbeat(&mut self, _online: &HashMap<String, String>) {}
+
+    fn on_recent_character_ids(&mut self, _character_ids: &[CharacterId]) {}
+
+    fn on_service_state_changed(&mut self, _online: bool, _detail: &str) {}
+
+    fn on_subscription(&mut self, _subscription: &Subscription) {}
+
+    fn on_achievement_earned(&mut self, _event: &AchievementEarned) {}
+
+    fn on_battle_rank_up(&mut self, _event: &BattleRankUp) {}
+
+    fn on_death(&mut self, _event: &Death) {}
+
+    fn on_item_added(&mut self, _event: &ItemAdded) {}
+
+    fn on_skill_added(&mut self, _event: &SkillAdded) {}
+
+    fn on_vehicle_destroy(&mut self, _event: &VehicleDestroy) {}
+
+    fn on_gain_experience(&mut self, _event: &GainExperience) {}
+
+    fn on_player_facility_capture(&mut self, _event: &PlayerFacilityCapture) {}
+
+    fn on_player_facility_defend(&mut self, _event: &PlayerFacilityDefend) {}
+
+    fn on_continent_lock(&mut self, _event: &ContinentLock) {}
+
+    fn on_continent_unlock(&mut self, _event: &ContinentUnlock) {}
+
+    fn on_facility_control(&mut self, _event: &FacilityControl) {}
+
+    fn on_metagame_event(&mut self, _event: &MetagameEvent) {}
+
+    fn on_player_login(&mut self, _event: &PlayerLogin) {}
+
+    fn on_player_logout(&mut self, _event: &PlayerLogout) {}
+}
+
+/// Routes a message to the handler callback for its kind, and events to `on_event`.
+pub fn dispatch<H>(handler: &mut H, message: &Message)
+where
+    H: EventHandler + ?Sized,
+{
+    match *message {
+        Message::ConnectionStateChanged { connected } => {
+            handler.on_connection_state_changed(connected)
+        }
+        Message::Heartbeat { ref online } => handler.on_heartbeat(online),
+        Message::RecentCharacterIds {
+            ref recent_character_id_list,
+        } => handler.on_recent_character_ids(recent_character_id_list),
+        Message::ServiceMessage { ref payload } => handler.on_event(payload),
+        Message::ServiceStateChanged { online, ref detail } => {
+            handler.on_service_state_changed(online, detail)
+        }
+        Message::Subscription { ref subscription } => handler.on_subscription(subscription),
+    }
+}
+
+/// Routes an event to the handler callback for its kind.
+pub fn dispatch_event<H>(handler: &mut H, event: &Event)
+where
+    H: EventHandler + ?Sized,
+{
+    match *event {
+        Event::AchievementEarned(ref event) => handler.on_achievement_earned(event),
+        Event::BattleRankUp(ref event) => handler.on_battle_rank_up(event),
+        Event::Death(ref event) => handler.on_death(event),
+        Event::ItemAdded(ref event) => handler.on_item_added(event),
+        Event::SkillAdded(ref event) => handler.on_skill_added(event),
+        Event::VehicleDestroy(ref event) => handler.on_vehicle_destroy(event),
+        Event::GainExperience(ref event) => handler.on_gain_experience(event),
+        Event::PlayerFacilityCapture(ref event) => handler.on_player_facility_capture(event),
+        Event::PlayerFacilityDefend(ref event) => handler.on_player_facility_defend(event),
+        Event::ContinentLock(ref event) => handler.on_continent_lock(event),
+        Event::ContinentUnlock(ref event) => handler.on_continent_unlock(event),
+        Event::FacilityControl(ref event) => handler.on_facility_control(event),
+        Event::MetagameEvent(ref event) => handler.on_metagame_event(event),
+        Event::PlayerLogin(ref event) => handler.on_player_login(event),
+        Event::PlayerLogout(ref event) => handler.on_player_logout(event),
+    }
+}
+
+/// Implements every `EventHandler` callback by passing it on with the `$forward` macro, which
+/// gets the optional `$context` and the call to make on `self`.
+macro_rules! forward_event_handler {
+    ($forward:ident!($($context:tt)*)) => {
+        fn on_message(&mut self, message: &Message) {
+            $forward!($($context)* self.on_message(message))
+        }
+
+        fn on_event(&mut self, event: &Event) {
+            $forward!($($context)* self.on_event(event))
+        }
+
+        fn on_connection_state_changed(&mut self, connected: bool) {
+            $forward!($($context)* self.on_connection_state_changed(connected))
+        }
+
+        fn on_heartbeat(&mut self, online: &HashMap<String, String>) {
+            $forward!($($context)* self.on_heartbeat(online))
+        }
+
+        fn on_recent_character_ids(&mut self, character_ids: &[CharacterId]) {
+            $forward!($($context)* self.on_recent_character_ids(character_ids))
+        }
+
+        fn on_service_state_changed(&mut self, online: bool, detail: &str) {
+            $forward!($($context)* self.on_service_state_changed(online, detail))
+        }
+
+        fn on_subscription(&mut self, subscription: &Subscription) {
+            $forward!($($context)* self.on_subscription(subscription))
+        }
+
+        fn on_achievement_earned(&mut self, event: &AchievementEarned) {
+            $forward!($($context)* self.on_achievement_earned(event))
+        }
+
+        fn on_battle_rank_up(&mut self, event: &BattleRankUp) {
+            $forward!($($context)* self.on_battle_rank_up(event))
+        }
+
+        fn on_death(&mut self, event: &Death) {
+            $forward!($($context)* self.on_death(event))
+        }
+
+        fn on_item_added(&mut self, event: &ItemAdded) {
+            $forward!($($context)* self.on_item_added(event))
+        }
+
+        fn on_skill_added(&mut self, event: &SkillAdded) {
+            $forward!($($context)* self.on_skill_added(event))
+        }
+
+        fn on_vehicle_destroy(&mut self, event: &VehicleDestroy) {
+            $forward!($($context)* self.on_vehicle_destroy(event))
+        }
+
+        fn on_gain_experience(&mut self, event: &GainExperience) {
+            $forward!($($context)* self.on_gain_experience(event))
+        }
+
+        fn on_player_facility_capture(&mut self, event: &PlayerFacilityCapture) {
+            $forward!($($context)* self.on_player_facility_capture(event))
+        }
+
+        fn on_player_facility_defend(&mut self, event: &PlayerFacilityDefend) {
+            $forward!($($context)* self.on_player_facility_defend(event))
+        }
+
+        fn on_continent_lock(&mut self, event: &ContinentLock) {
+            $forward!($($context)* self.on_continent_lock(event))
+        }
+
+        fn on_continent_unlock(&mut self, event: &ContinentUnlock) {
+            $forward!($($context)* self.on_continent_unlock(event))
+        }
+
+        fn on_facility_control(&mut self, event: &FacilityControl) {
+            $forward!($($context)* self.on_facility_control(event))
+        }
+
+        fn on_metagame_event(&mut self, event: &MetagameEvent) {
+            $forward!($($context)* self.on_metagame_event(event))
+        }
+
+        fn on_player_login(&mut self, event: &PlayerLogin) {
+            $forward!($($context)* self.on_player_login(event))
+        }
+
+        fn on_player_logout(&mut self, event: &PlayerLogout) {
+            $forward!($($context)* self.on_player_logout(event))
+        }
+    };
+}
+
+macro_rules! forward_deref {
+    ($this:ident.$method:ident $args:tt) => {
+        (**$this).$method $args
+    };
+}
+
+macro_rules! forward_iter {
+    ($this:ident.$method:ident $args:tt) => {
+        for handler in $this.iter_mut() {
+            handler.$method $args;
+        }
+    };
+}
+
+macro_rules! forward_tuple {
+    ([$($name:ident)+] $this:ident.$method:ident $args:tt) => {{
+        let ($(ref mut $name,)+) = *$this;
+        $($name.$method $args;)+
+    }};
+}
+
+impl<H> EventHandler for &mut H
+where
+    H: EventHandler + ?Sized,
+{
+    forward_event_handler!(forward_deref!());
+}
+
+impl<H> EventHandler for Box<H>
+where
+    H: EventHandler + ?Sized,
+{
+    forward_event_handler!(forward_deref!());
+}
+
+/// Forwards to each handler in order.
+impl<H> EventHandler for Vec<H>
+where
+    H: EventHandler,
+{
+    forward_event_handler!(forward_iter!());
+}
+
+macro_rules! tuple_event_handler {
+    ($($name:ident)+) => {
+        /// Forwards to each handler in order.
+        #[allow(non_snake_case)]
+        impl<$($name),+> EventHandler for ($($name,)+)
+        where
+            $($name: EventHandler,)+
+        {
+            forward_event_handler!(forward_tuple!([$($name)+]));
+        }
+    };
+}
+
+tuple_event_handler!(A);
+tuple_event_handler!(A B);
+tuple_event_handler!(A B C);
+tuple_event_handler!(A B C D);
+tuple_event_handler!(A B C D E);
+tuple_event_handler!(A B C D E F);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[derive(Default)]
+    struct Counts {
+        deaths: u64,
+        logins: u64,
+        heartbeats: u64,
+    }
+
+    impl EventHandler for Counts {
+        fn on_death(&mut self, _event: &Death) {
+            self.deaths += 1;
+        }
+
+        fn on_player_login(&mut self, _event: &PlayerLogin) {
+            self.logins += 1;
+        }
+
+        fn on_heartbeat(&mut self, _online: &HashMap<String, String>) {
+            self.heartbeats += 1;
+        }
+    }
+
+    fn messages() -> Vec<Message> {
+        let messages = json!([
+            {
+                "online": {"EventServerEndpoint_Connery_1": "true"},
+                "service": "event",
+                "type": "heartbeat"
+            },
+            {
+                "payload": {
+                    "character_id": "5428602376718262177",
+                    "event_name": "PlayerLogin",
+                    "timestamp": "1513785744",
+                    "world_id": "1"
+                },
+                "service": "event",
+                "type": "serviceMessage"
+            },
+            {
+                "payload": {
+                    "character_id": "5428602376718262177",
+                    "event_name": "PlayerLogout",
+                    "timestamp": "1513785790",
+                    "world_id": "1"
+                },
+                "service": "event",
+                "type": "serviceMessage"
+            }
+        ]);
+
+        serde_json::from_value(messages).unwrap()
+    }
+
+    #[test]
+    fn routes_to_callbacks() {
+        let mut counts = Counts::default();
+        for message in &messages() {
+            counts.on_message(message);
+        }
+
+        assert_eq!(counts.heartbeats, 1);
+        assert_eq!(counts.logins, 1);
+        assert_eq!(counts.deaths, 0);
+    }
+
+    #[test]
+    fn composes() {
+        let mut boxed: Vec<Box<Counts>> = vec![Box::new(Counts::default())];
+        let mut borrowed = Counts::default();
+        {
+            let mut handlers = (Counts::default(), &mut boxed, &mut borrowed);
+            for message in &messages() {
+                handlers.on_message(message);
+            }
+
+            assert_eq!(handlers.0.logins, 1);
+            assert_eq!(handlers.0.heartbeats, 1);
+        }
+
+        assert_eq!(boxed[0].logins, 1);
+        assert_eq!(boxed[0].heartbeats, 1);
+        assert_eq!(borrowed.logins, 1);
+        assert_eq!(borrowed.heartbeats, 1);
+    }
+
+    #[test]
+    fn forwards_callbacks() {
+        let death: Death = serde_json::from_value(json!({
+            "attacker_character_id": "5428010618015189713",
+            "attacker_fire_mode_id": "26003",
+            "attacker_loadout_id": "15",
+            "attacker_vehicle_id": "0",
+            "attacker_weapon_id": "26002",
+            "character_id": "5428713425545165425",
+            "character_loadout_id": "4",
+            "is_critical": "0",
+            "is_headshot": "1",
+            "timestamp": "1513785744",
+            "vehicle_id": "0",
+            "world_id": "13",
+            "zone_id": "2"
+        })).unwrap();
+
+        let mut boxed: Vec<Box<dyn EventHandler>> = vec![Box::new(Counts::default())];
+        let mut borrowed = Counts::default();
+        {
+            let mut handlers = (Counts::default(), &mut boxed, &mut borrowed);
+            handlers.on_death(&death);
+            handlers.on_heartbeat(&HashMap::new());
+
+            assert_eq!(handlers.0.deaths, 1);
+            assert_eq!(handlers.0.heartbeats, 1);
+        }
+
+        assert_eq!(borrowed.deaths, 1);
+        assert_eq!(borrowed.heartbeats, 1);
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use serde;
+
+macro_rules! id_newtype {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+        pub struct $name(u64);
+
+        impl $name {
+            pub const fn new(id: u64) -> $name {
+                $name(id)
+            }
+
+            pub fn get(self) -> u64 {
+                self.0
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(id: u64) -> $name {
+                $name(id)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(id: $name) -> u64 {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<$name, ParseIntError> {
+                s.parse().map($name)
+            }
+        }
+
+        /// Serializes as a string, like the push API does.
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_str(&self.0)
+            }
+        }
+
+        /// Deserializes from a string or a number.
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<$name, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(IdVisitor).map($name)
+            }
+        }
+    };
+}
+
+pub(crate) struct IdVisitor;
+
+impl<'de> serde::de::Visitor<'de> for IdVisitor {
+    type Value = u64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an id as string or number")
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<u64, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(value)
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<u64, E>
+    where
+        E: serde::de::Error,
+    {
+        value.parse().map_err(E::custom)
+    }
+}
+
+id_newtype!(CharacterId);
+id_newtype!(WorldId);
+id_newtype!(ExperienceId);
+id_newtype!(OutfitId);
+id_newtype!(FacilityId);
+id_newtype!(
+    /// Also used for weapons, which are items.
+    ItemId
+);
+id_newtype!(VehicleId);
+id_newtype!(AchievementId);
+id_newtype!(SkillId);
+id_newtype!(MetagameEventId);
+id_newtype!(LoadoutId);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn fromstr_display() {
+        let character_id: CharacterId = "5428010618015189713".parse().unwrap();
+        assert_eq!(character_id, CharacterId::new(5428010618015189713));
+        assert_eq!(character_id.to_string(), "5428010618015189713");
+
+        assert!("all".parse::<CharacterId>().is_err());
+    }
+
+    #[test]
+    fn serde() {
+        assert_eq!(serde_json::to_value(WorldId::new(13)).unwrap(), json!("13"));
+
+        assert_eq!(
+            serde_json::from_value::<WorldId>(json!("13")).unwrap(),
+            WorldId::new(13)
+        );
+        assert_eq!(
+            serde_json::from_value::<WorldId>(json!(13)).unwrap(),
+            WorldId::new(13)
+        );
+        assert!(serde_json::from_value::<WorldId>(json!("Cobalt")).is_err());
+    }
+}
@@ -0,0 +1,434 @@
+//! Kill feed and kill/death statistics from `Death` events.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::Duration;
+
+use handler::EventHandler;
+use response::{Death, DeathKind, Event};
+use {CharacterId, ItemId, Timestamp, VehicleId};
+
+/// Kill and death counts.
+///
+/// Teamkills and suicides are counted separately and not as kills. Weapon and vehicle stats only
+/// count kills, headshots and teamkills made with them.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct KillStats {
+    pub kills: u64,
+    pub deaths: u64,
+    pub headshots: u64,
+    pub teamkills: u64,
+    pub suicides: u64,
+}
+
+impl KillStats {
+    /// Kills per death, or just the kills without any deaths.
+    pub fn kill_death_ratio(&self) -> f64 {
+        match self.deaths {
+            0 => self.kills as f64,
+            deaths => self.kills as f64 / deaths as f64,
+        }
+    }
+
+    /// Headshots per kill, `0.0` without any kills.
+    pub fn headshot_ratio(&self) -> f64 {
+        match self.kills {
+            0 => 0.0,
+            kills => self.headshots as f64 / kills as f64,
+        }
+    }
+}
+
+/// How kills are grouped in time.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Window {
+    /// The deaths within the given duration before the latest one.
+    Sliding(Duration),
+    /// Consecutive windows of the given duration, aligned to the Unix epoch, e.g. UTC days.
+    ///
+    /// The duration is truncated to whole seconds, but at least one.
+    Tumbling(Duration),
+}
+
+/// The kill statistics of a time window.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KillBoard {
+    pub start: Timestamp,
+    /// The end of the window, or the latest representable timestamp if it is later.
+    pub end: Timestamp,
+    pub characters: HashMap<CharacterId, KillStats>,
+    pub weapons: HashMap<ItemId, KillStats>,
+    pub vehicles: HashMap<VehicleId, KillStats>,
+}
+
+impl KillBoard {
+    fn new(start: Timestamp, end: Timestamp) -> KillBoard {
+        KillBoard {
+            start,
+            end,
+            characters: HashMap::new(),
+            weapons: HashMap::new(),
+            vehicles: HashMap::new(),
+        }
+    }
+
+    pub fn character(&self, character_id: CharacterId) -> KillStats {
+        self.characters
+            .get(&character_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn weapon(&self, weapon_id: ItemId) -> KillStats {
+        self.weapons.get(&weapon_id).cloned().unwrap_or_default()
+    }
+
+    pub fn vehicle(&self, vehicle_id: VehicleId) -> KillStats {
+        self.vehicles.get(&vehicle_id).cloned().unwrap_or_default()
+    }
+
+    /// Adds or, for `removed`, subtracts a death.
+    fn count(&mut self, death: &Death, removed: bool) {
+        let kind = death.kind();
+
+        let victim = KillStats {
+            deaths: 1,
+            suicides: if kind == DeathKind::Suicide { 1 } else { 0 },
+            ..KillStats::default()
+        };
+        update(&mut self.characters, death.character_id, victim, removed);
+
+        let attacker = match kind {
+            DeathKind::Kill => KillStats {
+                kills: 1,
+                headshots: if death.is_headshot { 1 } else { 0 },
+                ..KillStats::default()
+            },
+            DeathKind::Teamkill => KillStats {
+                teamkills: 1,
+                ..KillStats::default()
+            },
+            DeathKind::Suicide | DeathKind::Environmental => return,
+        };
+        update(&mut self.characters, death.attacker_character_id, attacker, removed);
+        if death.attacker_weapon_id.get() != 0 {
+            update(&mut self.weapons, death.attacker_weapon_id, attacker, removed);
+        }
+        if death.attacker_vehicle_id.get() != 0 {
+            update(&mut self.vehicles, death.attacker_vehicle_id, attacker, removed);
+        }
+    }
+}
+
+fn update<K>(stats: &mut HashMap<K, KillStats>, key: K, delta: KillStats, removed: bool)
+where
+    K: Hash + Eq,
+{
+    if !removed {
+        let stats = stats.entry(key).or_default();
+        stats.kills += delta.kills;
+        stats.deaths += delta.deaths;
+        stats.headshots += delta.headshots;
+        stats.teamkills += delta.teamkills;
+        stats.suicides += delta.suicides;
+        return;
+    }
+
+    if let Entry::Occupied(mut entry) = stats.entry(key) {
+        {
+            let stats = entry.get_mut();
+            stats.kills -= delta.kills;
+            stats.deaths -= delta.deaths;
+            stats.headshots -= delta.headshots;
+            stats.teamkills -= delta.teamkills;
+            stats.suicides -= delta.suicides;
+        }
+        if *entry.get() == KillStats::default() {
+            entry.remove();
+        }
+    }
+}
+
+/// Aggregates `Death` events into a kill feed and `KillBoard`s over a `Window`.
+///
+/// Deaths are expected in roughly chronological order. Deaths from before the current window are
+/// dropped, and with a tumbling window, each completed window is kept until taken with `drain`.
+#[derive(Clone, Debug)]
+pub struct KillAggregator {
+    window: Window,
+    feed: VecDeque<Death>,
+    board: Option<KillBoard>,
+    completed: Vec<KillBoard>,
+}
+
+impl KillAggregator {
+    pub fn new(window: Window) -> KillAggregator {
+        KillAggregator {
+            window,
+            feed: VecDeque::new(),
+            board: None,
+            completed: Vec::new(),
+        }
+    }
+
+    pub fn handle_death(&mut self, death: &Death) {
+        match self.window {
+            Window::Sliding(duration) => {
+                if self.board
+                    .as_ref()
+                    .map_or(false, |board| death.timestamp < board.start)
+                {
+                    return;
+                }
+
+                let board = self.board
+                    .get_or_insert_with(|| KillBoard::new(death.timestamp, death.timestamp));
+                board.count(death, false);
+                // Keep the feed ordered by time, so that late deaths are evicted in time as well.
+                let index = self.feed
+                    .iter()
+                    .rposition(|later| later.timestamp <= death.timestamp)
+                    .map_or(0, |index| index + 1);
+                self.feed.insert(index, death.clone());
+
+                if board.end < death.timestamp {
+                    board.end = death.timestamp;
+                }
+                board.start = board.end.checked_sub(duration).unwrap_or(Timestamp::UNIX_EPOCH);
+                while self.feed
+                    .front()
+                    .map_or(false, |oldest| oldest.timestamp < board.start)
+                {
+                    if let Some(oldest) = self.feed.pop_front() {
+                        board.count(&oldest, true);
+                    }
+                }
+            }
+            Window::Tumbling(duration) => {
+                let (start, end) = tumbling_bounds(death.timestamp, duration);
+                match self.board {
+                    Some(ref board) if start < board.start => return,
+                    Some(ref board) if start == board.start => {}
+                    _ => {
+                        let board = KillBoard::new(start, end);
+                        if let Some(board) = self.board.replace(board) {
+                            self.completed.push(board);
+                        }
+                        self.feed.clear();
+                    }
+                }
+
+                if let Some(ref mut board) = self.board {
+                    board.count(death, false);
+                }
+                self.feed.push_back(death.clone());
+            }
+        }
+    }
+
+    /// The deaths of the current window, oldest first.
+    pub fn feed(&self) -> impl Iterator<Item = &Death> {
+        self.feed.iter()
+    }
+
+    /// The statistics of the current window, if there were any deaths yet.
+    pub fn board(&self) -> Option<&KillBoard> {
+        self.board.as_ref()
+    }
+
+    /// Takes the completed tumbling windows, oldest first.
+    pub fn drain(&mut self) -> ::std::vec::Drain<'_, KillBoard> {
+        self.completed.drain(..)
+    }
+}
+
+impl EventHandler for KillAggregator {
+    fn on_death(&mut self, event: &Death) {
+        self.handle_death(event)
+    }
+}
+
+/// Adapts a stream of events into its deaths along with their `DeathKind`.
+///
+/// ```
+/// use planetside2_push::kills::classify;
+/// use planetside2_push::response::{DeathKind, Event};
+///
+/// # let events: Vec<Event> = Vec::new();
+/// let teamkills = classify(events).filter(|&(_, kind)| kind == DeathKind::Teamkill);
+/// # assert_eq!(teamkills.count(), 0);
+/// ```
+pub fn classify<I>(events: I) -> Classify<I::IntoIter>
+where
+    I: IntoIterator<Item = Event>,
+{
+    Classify {
+        events: events.into_iter(),
+    }
+}
+
+/// The iterator returned by `classify`.
+#[derive(Clone, Debug)]
+pub struct Classify<I> {
+    events: I,
+}
+
+impl<I> Iterator for Classify<I>
+where
+    I: Iterator<Item = Event>,
+{
+    type Item = (Death, DeathKind);
+
+    fn next(&mut self) -> Option<(Death, DeathKind)> {
+        for event in &mut self.events {
+            if let Event::Death(death) = event {
+                let kind = death.kind();
+                return Some((death, kind));
+            }
+        }
+
+        None
+    }
+}
+
+/// The start and end of the tumbling window that contains `timestamp`, with the end saturating at
+/// the latest representable timestamp.
+fn tumbling_bounds(timestamp: Timestamp, duration: Duration) -> (Timestamp, Timestamp) {
+    let secs = duration.as_secs().max(1);
+    let start = timestamp.as_secs() / secs * secs;
+    (
+        Timestamp::from_secs(start),
+        Timestamp::from_secs(start.saturating_add(secs)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zone::ZoneId;
+    use {LoadoutId, WorldId};
+
+    fn death(attacker: (u64, u64), victim: (u64, u64), weapon: u64, timestamp: u64) -> Death {
+        Death {
+            attacker_character_id: CharacterId::new(attacker.0),
+            attacker_fire_mode_id: 0,
+            attacker_loadout_id: LoadoutId::new(attacker.1),
+            attacker_team_id: None,
+            attacker_vehicle_id: VehicleId::new(0),
+            attacker_weapon_id: ItemId::new(weapon),
+            character_id: CharacterId::new(victim.0),
+            character_loadout_id: LoadoutId::new(victim.1),
+            is_critical: false,
+            is_headshot: true,
+            team_id: None,
+            timestamp: Timestamp::from_secs(timestamp),
+            vehicle_id: VehicleId::new(0),
+            world_id: WorldId::new(13),
+            zone_id: ZoneId::new(2),
+        }
+    }
+
+    #[test]
+    fn classify_events() {
+        let events = vec![
+            Event::Death(death((1, 15), (2, 17), 7, 100)),
+            Event::Death(death((1, 15), (2, 8), 7, 110)),
+        ];
+
+        assert_eq!(
+            classify(events).map(|(_, kind)| kind).collect::<Vec<_>>(),
+            vec![DeathKind::Teamkill, DeathKind::Kill]
+        );
+    }
+
+    #[test]
+    fn sliding() {
+        let mut aggregator = KillAggregator::new(Window::Sliding(Duration::from_secs(60)));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 100));
+        aggregator.handle_death(&death((1, 15), (3, 17), 7, 110));
+        aggregator.handle_death(&death((2, 8), (1, 15), 9, 120));
+        aggregator.handle_death(&death((2, 8), (2, 8), 0, 130));
+
+        let board = aggregator.board().unwrap();
+        assert_eq!(
+            board.character(CharacterId::new(1)),
+            KillStats {
+                kills: 1,
+                deaths: 1,
+                headshots: 1,
+                teamkills: 1,
+                suicides: 0,
+            }
+        );
+        assert_eq!(board.character(CharacterId::new(2)).suicides, 1);
+        assert_eq!(board.weapon(ItemId::new(7)).kills, 1);
+        assert_eq!(board.weapon(ItemId::new(7)).teamkills, 1);
+
+        aggregator.handle_death(&death((3, 17), (2, 8), 9, 175));
+        let board = aggregator.board().unwrap();
+        assert_eq!(board.start, Timestamp::from_secs(115));
+        assert_eq!(board.character(CharacterId::new(1)).kills, 0);
+        assert_eq!(board.character(CharacterId::new(1)).deaths, 1);
+        assert_eq!(board.weapon(ItemId::new(7)), KillStats::default());
+        assert_eq!(board.weapon(ItemId::new(9)).kills, 2);
+        assert_eq!(aggregator.feed().count(), 3);
+    }
+
+    #[test]
+    fn sliding_out_of_order() {
+        let mut aggregator = KillAggregator::new(Window::Sliding(Duration::from_secs(60)));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 100));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 170));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 105));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 150));
+
+        let board = aggregator.board().unwrap();
+        assert_eq!(board.start, Timestamp::from_secs(110));
+        assert_eq!(board.character(CharacterId::new(1)).kills, 2);
+        assert_eq!(
+            aggregator.feed().map(|death| death.timestamp.as_secs()).collect::<Vec<_>>(),
+            vec![150, 170]
+        );
+
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 215));
+        let board = aggregator.board().unwrap();
+        assert_eq!(board.character(CharacterId::new(1)).kills, 2);
+        assert_eq!(board.character(CharacterId::new(2)).deaths, 2);
+    }
+
+    #[test]
+    fn tumbling() {
+        let mut aggregator = KillAggregator::new(Window::Tumbling(Duration::from_secs(100)));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 150));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 199));
+        aggregator.handle_death(&death((2, 8), (1, 15), 7, 200));
+        aggregator.handle_death(&death((2, 8), (1, 15), 7, 50));
+
+        let completed: Vec<_> = aggregator.drain().collect();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].start, Timestamp::from_secs(100));
+        assert_eq!(completed[0].end, Timestamp::from_secs(200));
+        assert_eq!(completed[0].character(CharacterId::new(1)).kills, 2);
+
+        let board = aggregator.board().unwrap();
+        assert_eq!(board.start, Timestamp::from_secs(200));
+        assert_eq!(board.character(CharacterId::new(2)).kills, 1);
+        assert_eq!(aggregator.feed().count(), 1);
+    }
+
+    #[test]
+    fn tumbling_edges() {
+        let mut aggregator = KillAggregator::new(Window::Tumbling(Duration::from_millis(500)));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, 150));
+        let board = aggregator.board().unwrap();
+        assert_eq!(board.start, Timestamp::from_secs(150));
+        assert_eq!(board.end, Timestamp::from_secs(151));
+
+        let mut aggregator = KillAggregator::new(Window::Tumbling(Duration::from_secs(100)));
+        aggregator.handle_death(&death((1, 15), (2, 8), 7, u64::MAX - 10));
+        let board = aggregator.board().unwrap();
+        assert_eq!(board.end, Timestamp::from_secs(u64::MAX));
+        assert_eq!(board.character(CharacterId::new(1)).kills, 1);
+    }
+}
@@ -0,0 +1,312 @@
+//! Outfit leaderboards of facility captures and defenses.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use facility::FacilityMap;
+use handler::EventHandler;
+use response::{FacilityControl, PlayerFacilityCapture, PlayerFacilityDefend};
+use {CharacterId, OutfitId, Timestamp};
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The calendar periods leaderboards cover, in UTC.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Period {
+    Day,
+    /// A week starting on Monday.
+    Week,
+}
+
+impl Period {
+    pub fn duration(self) -> Duration {
+        match self {
+            Period::Day => Duration::from_secs(SECS_PER_DAY),
+            Period::Week => Duration::from_secs(7 * SECS_PER_DAY),
+        }
+    }
+
+    /// The start of the period that contains `timestamp`, but no earlier than the Unix epoch.
+    pub fn start(self, timestamp: Timestamp) -> Timestamp {
+        let days = timestamp.as_secs() / SECS_PER_DAY;
+        let days = match self {
+            Period::Day => days,
+            // The Unix epoch was a Thursday.
+            Period::Week => days.saturating_sub((days + 3) % 7),
+        };
+
+        Timestamp::from_secs(days * SECS_PER_DAY)
+    }
+}
+
+/// An outfit's standing on a `Leaderboard`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct OutfitStats {
+    /// Facilities captured by the outfit.
+    pub captures: u64,
+    /// Facilities defended by the outfit.
+    pub defenses: u64,
+    /// Distinct members that took part in captures or defenses.
+    pub participants: usize,
+    /// Facilities owned by the outfit at the end of the period, or now for the current period.
+    pub facilities_held: usize,
+}
+
+/// The outfit statistics of one period.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Leaderboard {
+    pub start: Timestamp,
+    /// The end of the period, or the latest representable timestamp if it is later.
+    pub end: Timestamp,
+    pub outfits: HashMap<OutfitId, OutfitStats>,
+}
+
+impl Leaderboard {
+    /// The outfits ordered by captures, then defenses, then participants, best first.
+    pub fn ranked(&self) -> Vec<(OutfitId, OutfitStats)> {
+        let mut ranked: Vec<_> = self.outfits
+            .iter()
+            .map(|(&outfit_id, &stats)| (outfit_id, stats))
+            .collect();
+        ranked.sort_by(|&(a_id, a), &(b_id, b)| {
+            (b.captures, b.defenses, b.participants)
+                .cmp(&(a.captures, a.defenses, a.participants))
+                .then(a_id.cmp(&b_id))
+        });
+
+        ranked
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Tally {
+    captures: u64,
+    defenses: u64,
+    participants: HashSet<CharacterId>,
+}
+
+/// Aggregates facility events into per-outfit `Leaderboard`s of a `Period`.
+///
+/// Captures and defenses are credited to the `outfit_id` of `FacilityControl`, participants to
+/// the `outfit_id` of `PlayerFacilityCapture` and `PlayerFacilityDefend`. Events from before the
+/// current period are dropped, and each completed period is kept until taken with `drain`. Run
+/// one leaderboard per period, e.g. as a tuple of handlers, for both daily and weekly standings.
+#[derive(Clone, Debug)]
+pub struct OutfitLeaderboard {
+    period: Period,
+    start: Option<Timestamp>,
+    outfits: HashMap<OutfitId, Tally>,
+    facilities: FacilityMap,
+    completed: Vec<Leaderboard>,
+}
+
+impl OutfitLeaderboard {
+    pub fn new(period: Period) -> OutfitLeaderboard {
+        OutfitLeaderboard {
+            period,
+            start: None,
+            outfits: HashMap::new(),
+            facilities: FacilityMap::new(),
+            completed: Vec::new(),
+        }
+    }
+
+    pub fn handle_facility_control(&mut self, event: &FacilityControl) {
+        let current = self.roll(event.timestamp);
+        self.facilities.handle_facility_control(event);
+
+        if !current || event.outfit_id.get() == 0 {
+            return;
+        }
+        let tally = self.outfits.entry(event.outfit_id).or_default();
+        if event.new_faction_id != event.old_faction_id {
+            tally.captures += 1;
+        } else {
+            tally.defenses += 1;
+        }
+    }
+
+    pub fn handle_player_facility_capture(&mut self, event: &PlayerFacilityCapture) {
+        self.participated(event.outfit_id, event.character_id, event.timestamp)
+    }
+
+    pub fn handle_player_facility_defend(&mut self, event: &PlayerFacilityDefend) {
+        self.participated(event.outfit_id, event.character_id, event.timestamp)
+    }
+
+    fn participated(
+        &mut self,
+        outfit_id: OutfitId,
+        character_id: CharacterId,
+        timestamp: Timestamp,
+    ) {
+        if self.roll(timestamp) && outfit_id.get() != 0 {
+            self.outfits
+                .entry(outfit_id)
+                .or_default()
+                .participants
+                .insert(character_id);
+        }
+    }
+
+    /// Moves on to the period of `timestamp` if it is a later one, returning whether `timestamp`
+    /// is in the current period.
+    fn roll(&mut self, timestamp: Timestamp) -> bool {
+        let start = self.period.start(timestamp);
+        match self.start {
+            Some(current) if start < current => false,
+            Some(current) if start == current => true,
+            _ => {
+                if let Some(leaderboard) = self.leaderboard() {
+                    self.completed.push(leaderboard);
+                }
+                self.outfits.clear();
+                self.start = Some(start);
+
+                true
+            }
+        }
+    }
+
+    /// The standings of the current period, if there were any events yet.
+    pub fn leaderboard(&self) -> Option<Leaderboard> {
+        let start = self.start?;
+
+        let mut held = HashMap::new();
+        for (_, _, _, ownership) in self.facilities.iter() {
+            if let Some(outfit_id) = ownership.outfit_id() {
+                *held.entry(outfit_id).or_insert(0) += 1;
+            }
+        }
+
+        let outfits = self.outfits
+            .iter()
+            .map(|(&outfit_id, tally)| {
+                let stats = OutfitStats {
+                    captures: tally.captures,
+                    defenses: tally.defenses,
+                    participants: tally.participants.len(),
+                    facilities_held: held.get(&outfit_id).cloned().unwrap_or(0),
+                };
+                (outfit_id, stats)
+            })
+            .collect();
+
+        Some(Leaderboard {
+            start,
+            end: start
+                .checked_add(self.period.duration())
+                .unwrap_or(Timestamp::from_secs(u64::MAX)),
+            outfits,
+        })
+    }
+
+    /// Takes the leaderboards of completed periods, oldest first.
+    pub fn drain(&mut self) -> ::std::vec::Drain<'_, Leaderboard> {
+        self.completed.drain(..)
+    }
+}
+
+impl EventHandler for OutfitLeaderboard {
+    fn on_facility_control(&mut self, event: &FacilityControl) {
+        self.handle_facility_control(event)
+    }
+
+    fn on_player_facility_capture(&mut self, event: &PlayerFacilityCapture) {
+        self.handle_player_facility_capture(event)
+    }
+
+    fn on_player_facility_defend(&mut self, event: &PlayerFacilityDefend) {
+        self.handle_player_facility_defend(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::{facility_control, player_capture};
+
+    const MONDAY: u64 = 1513555200;
+
+    #[test]
+    fn period_start() {
+        let wednesday = Timestamp::from_secs(MONDAY + 2 * SECS_PER_DAY + 3600);
+        assert_eq!(
+            Period::Day.start(wednesday),
+            Timestamp::from_secs(MONDAY + 2 * SECS_PER_DAY)
+        );
+        assert_eq!(Period::Week.start(wednesday), Timestamp::from_secs(MONDAY));
+        assert_eq!(
+            Period::Week.start(Timestamp::from_secs(MONDAY)),
+            Timestamp::from_secs(MONDAY)
+        );
+
+        assert_eq!(Period::Day.start(Timestamp::UNIX_EPOCH), Timestamp::UNIX_EPOCH);
+        assert_eq!(Period::Week.start(Timestamp::UNIX_EPOCH), Timestamp::UNIX_EPOCH);
+        assert_eq!(
+            Period::Week.start(Timestamp::from_secs(3 * SECS_PER_DAY)),
+            Timestamp::UNIX_EPOCH
+        );
+    }
+
+    #[test]
+    fn daily() {
+        let mut leaderboard = OutfitLeaderboard::new(Period::Day);
+        leaderboard.handle_facility_control(&facility_control(1, 3, 2, 10, 0, MONDAY + 100));
+        leaderboard.handle_player_facility_capture(&player_capture(1, 1, 10, MONDAY + 100));
+        leaderboard.handle_player_facility_capture(&player_capture(2, 1, 10, MONDAY + 100));
+        leaderboard.handle_player_facility_capture(&player_capture(3, 1, 0, MONDAY + 100));
+        leaderboard.handle_facility_control(&facility_control(2, 3, 3, 20, 0, MONDAY + 200));
+        leaderboard.handle_facility_control(&facility_control(3, 1, 3, 20, 0, MONDAY + 300));
+        leaderboard.handle_facility_control(&facility_control(4, 1, 3, 20, 0, MONDAY + 400));
+
+        let current = leaderboard.leaderboard().unwrap();
+        assert_eq!(current.start, Timestamp::from_secs(MONDAY));
+        assert_eq!(
+            current.ranked(),
+            vec![
+                (
+                    OutfitId::new(20),
+                    OutfitStats {
+                        captures: 2,
+                        defenses: 1,
+                        participants: 0,
+                        facilities_held: 2,
+                    },
+                ),
+                (
+                    OutfitId::new(10),
+                    OutfitStats {
+                        captures: 1,
+                        defenses: 0,
+                        participants: 2,
+                        facilities_held: 1,
+                    },
+                ),
+            ]
+        );
+
+        let next_day = MONDAY + SECS_PER_DAY;
+        leaderboard.handle_facility_control(&facility_control(1, 2, 3, 20, 0, next_day));
+        leaderboard.handle_player_facility_capture(&player_capture(1, 1, 10, MONDAY + 500));
+
+        let completed: Vec<_> = leaderboard.drain().collect();
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].outfits[&OutfitId::new(10)].facilities_held, 1);
+
+        let current = leaderboard.leaderboard().unwrap();
+        assert_eq!(current.outfits.len(), 1);
+        assert_eq!(current.outfits[&OutfitId::new(20)].facilities_held, 3);
+    }
+
+    #[test]
+    fn far_future() {
+        let mut leaderboard = OutfitLeaderboard::new(Period::Week);
+        leaderboard.handle_facility_control(&facility_control(1, 3, 2, 10, 0, u64::MAX - 10));
+
+        let current = leaderboard.leaderboard().unwrap();
+        assert_eq!(current.start, Period::Week.start(Timestamp::from_secs(u64::MAX)));
+        assert_eq!(current.end, Timestamp::from_secs(u64::MAX));
+        assert_eq!(current.outfits[&OutfitId::new(10)].captures, 1);
+    }
+}
@@ -5,23 +5,53 @@ extern crate serde_derive;
 extern crate serde_json;
 extern crate websocket;
 
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "time")]
+extern crate time;
+
+use std::fmt;
+
+#[cfg(test)]
+mod fixtures;
+mod id;
+mod timestamp;
+
+pub mod alert;
+pub mod continent;
+pub mod experience;
+pub mod facility;
+pub mod faction;
+pub mod handler;
+pub mod kills;
+pub mod leaderboard;
+pub mod loadout;
+pub mod population;
 pub mod request;
 pub mod response;
+pub mod session;
+pub mod world;
+pub mod zone;
+
+pub use id::{AchievementId, CharacterId, ExperienceId, FacilityId, ItemId, LoadoutId,
+             MetagameEventId, OutfitId, SkillId, VehicleId, WorldId};
 
+/// A raw id of a kind that has no dedicated type.
 pub type Id = u64;
-pub type CharacterId = Id;
-pub type WorldId = Id;
-pub type ExperienceId = Id;
 
-pub type Timestamp = u64;
+pub use timestamp::{ParseTimestampError, Timestamp, TryFromSystemTimeError, TryFromTimestampError};
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum Service {
     Event,
     Push,
 }
 
+/// A borrowed service id, e.g. from a command line argument.
+///
+/// Service ids are secret, so its `Debug` and `Display` output never contains the secret part.
+/// `ServiceId::from_env` reads an owned service id from the environment instead.
 pub struct ServiceId<'a>(&'a str);
 
 impl<'a> ServiceId<'a> {
@@ -29,19 +59,28 @@ impl<'a> ServiceId<'a> {
 
     pub const EXAMPLE: ServiceId<'static> = ServiceId(ServiceId::EXAMPLE_ID);
 
-    // TODO: FromStr
-    // TODO: AsRef<str>
-    pub fn new(service_id: &str) -> Result<ServiceId, ()> {
+    pub fn new(service_id: &'a str) -> Result<ServiceId<'a>, ParseServiceIdError> {
         if service_id.starts_with("s:") {
             Ok(ServiceId(service_id))
         } else {
-            Err(())
+            Err(ParseServiceIdError(()))
         }
     }
 
+    /// Reads the service id from the `PLANETSIDE2_SERVICE_ID` environment variable.
+    ///
+    /// The value is owned, since nothing in the environment can be borrowed for `'a`.
+    pub fn from_env() -> Result<ServiceIdBuf, FromEnvError> {
+        ServiceIdBuf::from_env()
+    }
+
     pub fn is_example(&self) -> bool {
         ServiceId::EXAMPLE_ID == self.0
     }
+
+    pub fn to_service_id_buf(&self) -> ServiceIdBuf {
+        ServiceIdBuf(self.0.to_string())
+    }
 }
 
 impl<'a> AsRef<str> for ServiceId<'a> {
@@ -50,6 +89,146 @@ impl<'a> AsRef<str> for ServiceId<'a> {
     }
 }
 
+impl<'a> fmt::Debug for ServiceId<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ServiceId").field(&Redacted(self.0)).finish()
+    }
+}
+
+impl<'a> fmt::Display for ServiceId<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Redacted(self.0), f)
+    }
+}
+
+/// An owned `ServiceId`, e.g. for storing in long-lived clients and configs.
+///
+/// Like `ServiceId`, its `Debug` and `Display` output never contains the secret part.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ServiceIdBuf(String);
+
+impl ServiceIdBuf {
+    /// Environment variable read by `ServiceId::from_env` and `ServiceIdBuf::from_env`.
+    pub const ENV_VAR: &'static str = "PLANETSIDE2_SERVICE_ID";
+
+    pub fn new(service_id: String) -> Result<ServiceIdBuf, ParseServiceIdError> {
+        ServiceId::new(&service_id)?;
+
+        Ok(ServiceIdBuf(service_id))
+    }
+
+    /// Reads the service id from the `PLANETSIDE2_SERVICE_ID` environment variable.
+    pub fn from_env() -> Result<ServiceIdBuf, FromEnvError> {
+        let service_id = std::env::var(ServiceIdBuf::ENV_VAR).map_err(FromEnvError::Var)?;
+
+        ServiceIdBuf::new(service_id).map_err(FromEnvError::Parse)
+    }
+
+    pub fn as_service_id(&self) -> ServiceId<'_> {
+        ServiceId(&self.0)
+    }
+
+    pub fn is_example(&self) -> bool {
+        self.as_service_id().is_example()
+    }
+}
+
+impl AsRef<str> for ServiceIdBuf {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'a> From<ServiceId<'a>> for ServiceIdBuf {
+    fn from(service_id: ServiceId<'a>) -> ServiceIdBuf {
+        service_id.to_service_id_buf()
+    }
+}
+
+impl std::str::FromStr for ServiceIdBuf {
+    type Err = ParseServiceIdError;
+
+    fn from_str(s: &str) -> Result<ServiceIdBuf, ParseServiceIdError> {
+        ServiceId::new(s).map(|service_id| service_id.to_service_id_buf())
+    }
+}
+
+impl fmt::Debug for ServiceIdBuf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ServiceIdBuf").field(&Redacted(&self.0)).finish()
+    }
+}
+
+impl fmt::Display for ServiceIdBuf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Redacted(&self.0), f)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ServiceIdBuf {
+    fn deserialize<D>(deserializer: D) -> Result<ServiceIdBuf, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        ServiceIdBuf::new(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Formats a service id without its secret part, unless it is the public example id.
+struct Redacted<'a>(&'a str);
+
+impl<'a> fmt::Display for Redacted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == ServiceId::EXAMPLE_ID {
+            f.write_str(self.0)
+        } else {
+            f.write_str("s:[redacted]")
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Redacted<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseServiceIdError(());
+
+impl fmt::Display for ParseServiceIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("service id does not start with \"s:\"")
+    }
+}
+
+impl std::error::Error for ParseServiceIdError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromEnvError {
+    Var(std::env::VarError),
+    Parse(ParseServiceIdError),
+}
+
+impl fmt::Display for FromEnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FromEnvError::Var(ref err) => write!(f, "{}: {}", ServiceIdBuf::ENV_VAR, err),
+            FromEnvError::Parse(ref err) => write!(f, "{}: {}", ServiceIdBuf::ENV_VAR, err),
+        }
+    }
+}
+
+impl std::error::Error for FromEnvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            FromEnvError::Var(ref err) => Some(err),
+            FromEnvError::Parse(ref err) => Some(err),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum Environment {
     Pc,
     Ps4Us,
@@ -96,6 +275,52 @@ mod tests {
         assert!(!not_example_sid.is_example());
     }
 
+    #[test]
+    fn serviceid_redacted() {
+        let sid = ServiceId::new("s:secret").unwrap();
+        assert_eq!(sid.to_string(), "s:[redacted]");
+        assert_eq!(format!("{:?}", sid), "ServiceId(s:[redacted])");
+
+        assert_eq!(ServiceId::EXAMPLE.to_string(), "s:example");
+    }
+
+    #[test]
+    fn serviceidbuf_fromstr() {
+        let sid: ServiceIdBuf = "s:secret".parse().unwrap();
+        assert_eq!(sid.as_ref(), "s:secret");
+        assert_eq!(sid.to_string(), "s:[redacted]");
+        assert_eq!(format!("{:?}", sid), "ServiceIdBuf(s:[redacted])");
+
+        assert!("secret".parse::<ServiceIdBuf>().is_err());
+    }
+
+    #[test]
+    fn serviceidbuf_deserialize() {
+        let sid: ServiceIdBuf = serde_json::from_str(r#""s:example""#).unwrap();
+        assert!(sid.is_example());
+
+        assert!(serde_json::from_str::<ServiceIdBuf>(r#""example""#).is_err());
+    }
+
+    #[test]
+    fn serviceidbuf_from_env() {
+        std::env::set_var(ServiceIdBuf::ENV_VAR, "s:example");
+        assert!(ServiceIdBuf::from_env().unwrap().is_example());
+        assert!(ServiceId::from_env().unwrap().is_example());
+
+        std::env::set_var(ServiceIdBuf::ENV_VAR, "example");
+        assert_eq!(
+            ServiceIdBuf::from_env(),
+            Err(FromEnvError::Parse(ParseServiceIdError(())))
+        );
+
+        std::env::remove_var(ServiceIdBuf::ENV_VAR);
+        assert_eq!(
+            ServiceIdBuf::from_env(),
+            Err(FromEnvError::Var(std::env::VarError::NotPresent))
+        );
+    }
+
     #[test]
     fn websocket_endpoint() {
         let address = super::websocket_endpoint(Environment::Pc, &ServiceId::EXAMPLE);
@@ -0,0 +1,165 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use faction::Faction;
+use LoadoutId;
+
+/// The infantry classes, including the MAX.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Class {
+    Infiltrator,
+    LightAssault,
+    Medic,
+    Engineer,
+    HeavyAssault,
+    Max,
+}
+
+impl Class {
+    pub const ALL: [Class; 6] = [
+        Class::Infiltrator,
+        Class::LightAssault,
+        Class::Medic,
+        Class::Engineer,
+        Class::HeavyAssault,
+        Class::Max,
+    ];
+
+    pub fn name(self) -> &'static str {
+        use self::Class::*;
+
+        match self {
+            Infiltrator => "Infiltrator",
+            LightAssault => "Light Assault",
+            Medic => "Medic",
+            Engineer => "Engineer",
+            HeavyAssault => "Heavy Assault",
+            Max => "MAX",
+        }
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A `loadout_id` decoded into the faction and class it belongs to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Loadout {
+    pub faction: Faction,
+    pub class: Class,
+}
+
+const LOADOUTS: [(u64, Faction, Class); 24] = [
+    (1, Faction::Nc, Class::Infiltrator),
+    (3, Faction::Nc, Class::LightAssault),
+    (4, Faction::Nc, Class::Medic),
+    (5, Faction::Nc, Class::Engineer),
+    (6, Faction::Nc, Class::HeavyAssault),
+    (7, Faction::Nc, Class::Max),
+    (8, Faction::Tr, Class::Infiltrator),
+    (10, Faction::Tr, Class::LightAssault),
+    (11, Faction::Tr, Class::Medic),
+    (12, Faction::Tr, Class::Engineer),
+    (13, Faction::Tr, Class::HeavyAssault),
+    (14, Faction::Tr, Class::Max),
+    (15, Faction::Vs, Class::Infiltrator),
+    (17, Faction::Vs, Class::LightAssault),
+    (18, Faction::Vs, Class::Medic),
+    (19, Faction::Vs, Class::Engineer),
+    (20, Faction::Vs, Class::HeavyAssault),
+    (21, Faction::Vs, Class::Max),
+    (28, Faction::Nso, Class::Infiltrator),
+    (29, Faction::Nso, Class::LightAssault),
+    (30, Faction::Nso, Class::Medic),
+    (31, Faction::Nso, Class::Engineer),
+    (32, Faction::Nso, Class::HeavyAssault),
+    (45, Faction::Nso, Class::Max),
+];
+
+impl Loadout {
+    pub fn new(faction: Faction, class: Class) -> Loadout {
+        Loadout { faction, class }
+    }
+
+    pub fn id(self) -> LoadoutId {
+        LOADOUTS
+            .iter()
+            .find(|&&(_, faction, class)| faction == self.faction && class == self.class)
+            .map(|&(id, _, _)| LoadoutId::new(id))
+            .expect("every faction has every class")
+    }
+}
+
+impl TryFrom<LoadoutId> for Loadout {
+    type Error = TryFromLoadoutIdError;
+
+    /// Fails for unknown ids as well as for `0`, e.g. of attackers that are not players.
+    fn try_from(id: LoadoutId) -> Result<Loadout, TryFromLoadoutIdError> {
+        LOADOUTS
+            .iter()
+            .find(|&&(loadout_id, _, _)| loadout_id == id.get())
+            .map(|&(_, faction, class)| Loadout::new(faction, class))
+            .ok_or(TryFromLoadoutIdError(id))
+    }
+}
+
+impl fmt::Display for Loadout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.faction, self.class)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromLoadoutIdError(LoadoutId);
+
+impl fmt::Display for TryFromLoadoutIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown loadout id {}", self.0)
+    }
+}
+
+impl ::std::error::Error for TryFromLoadoutIdError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_loadout_id() {
+        assert_eq!(
+            Loadout::try_from(LoadoutId::new(4)),
+            Ok(Loadout::new(Faction::Nc, Class::Medic))
+        );
+        assert_eq!(
+            Loadout::try_from(LoadoutId::new(20)),
+            Ok(Loadout::new(Faction::Vs, Class::HeavyAssault))
+        );
+        assert_eq!(
+            Loadout::try_from(LoadoutId::new(45)),
+            Ok(Loadout::new(Faction::Nso, Class::Max))
+        );
+        assert!(Loadout::try_from(LoadoutId::new(0)).is_err());
+        assert!(Loadout::try_from(LoadoutId::new(2)).is_err());
+    }
+
+    #[test]
+    fn id() {
+        for faction in Faction::ALL.iter() {
+            for class in Class::ALL.iter() {
+                let loadout = Loadout::new(*faction, *class);
+                assert_eq!(Loadout::try_from(loadout.id()), Ok(loadout));
+            }
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            Loadout::new(Faction::Tr, Class::LightAssault).to_string(),
+            "TR Light Assault"
+        );
+    }
+}
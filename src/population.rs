@@ -0,0 +1,605 @@
+//! Tracking of online characters from `PlayerLogin` and `PlayerLogout` events, and estimation of
+//! faction populations from recent activity.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use faction::Faction;
+use handler::EventHandler;
+use loadout::Loadout;
+use request::Action;
+use response::{Event, Message};
+use zone::ZoneId;
+use {CharacterId, LoadoutId, Service, Timestamp, WorldId};
+
+/// What is known about an online character.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Presence {
+    /// The world of the character, unknown for seeded characters until they show up in an event.
+    pub world_id: Option<WorldId>,
+    /// When the character logged in, unknown unless its `PlayerLogin` was seen.
+    pub login: Option<Timestamp>,
+    /// When the character was last seen in any event.
+    pub last_seen: Timestamp,
+}
+
+/// Keeps the set of online characters per world.
+///
+/// Characters come online with `PlayerLogin` and go offline with `PlayerLogout`. Since logouts
+/// get lost, e.g. while disconnected, characters that have not been seen in any event for longer
+/// than the timeout are expired. Any event of a character counts as a sign of life, so subscribe
+/// to more than the login events to keep active characters from expiring.
+///
+/// Handling messages as an `EventHandler` expires characters on each heartbeat, relative to the
+/// latest event seen.
+#[derive(Clone, Debug)]
+pub struct PopulationTracker {
+    timeout: Duration,
+    characters: HashMap<CharacterId, Presence>,
+    latest: Option<Timestamp>,
+}
+
+impl PopulationTracker {
+    pub fn new(timeout: Duration) -> PopulationTracker {
+        PopulationTracker {
+            timeout,
+            characters: HashMap::new(),
+            latest: None,
+        }
+    }
+
+    /// The action that requests the characters to `seed` the tracker with.
+    pub fn seed_action() -> Action {
+        Action::RecentCharacterIds {
+            service: Service::Event,
+        }
+    }
+
+    /// Adds recently active characters, i.e. the reply to `seed_action`, as seen at `now`.
+    ///
+    /// The reply does not say which world the characters are on, so they only count towards
+    /// `total` until they show up in an event.
+    pub fn seed<I>(&mut self, character_ids: I, now: Timestamp)
+    where
+        I: IntoIterator<Item = CharacterId>,
+    {
+        for character_id in character_ids {
+            self.characters.entry(character_id).or_insert(Presence {
+                world_id: None,
+                login: None,
+                last_seen: now,
+            });
+        }
+    }
+
+    /// Seeds the tracker like `seed` if `reply` is the reply to `seed_action`, returning whether
+    /// it was.
+    pub fn seed_from(&mut self, reply: &Message, now: Timestamp) -> bool {
+        match *reply {
+            Message::RecentCharacterIds {
+                ref recent_character_id_list,
+            } => {
+                self.seed(recent_character_id_list.iter().cloned(), now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Updates the online characters from an event.
+    ///
+    /// Logins and logouts add and remove characters, though logouts older than the character's
+    /// last activity are ignored. The subject of any other character event is added or refreshed,
+    /// while other involved characters such as attackers are only refreshed, since e.g. the
+    /// `other_id` of experience is not always a character.
+    pub fn handle_event(&mut self, event: &Event) {
+        let timestamp = event.timestamp();
+        let world_id = event.world_id();
+        if self.latest.map_or(true, |latest| latest < timestamp) {
+            self.latest = Some(timestamp);
+        }
+
+        match *event {
+            Event::PlayerLogin(ref login) => match self.characters.entry(login.character_id) {
+                Entry::Occupied(mut entry) => {
+                    let presence = entry.get_mut();
+                    if presence.world_id != Some(world_id) || presence.login.is_none() {
+                        presence.login = Some(timestamp);
+                    }
+                    presence.world_id = Some(world_id);
+                    presence.last_seen = presence.last_seen.max(timestamp);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(Presence {
+                        world_id: Some(world_id),
+                        login: Some(timestamp),
+                        last_seen: timestamp,
+                    });
+                }
+            },
+            Event::PlayerLogout(ref logout) => {
+                if let Entry::Occupied(entry) = self.characters.entry(logout.character_id) {
+                    if timestamp >= entry.get().last_seen {
+                        entry.remove();
+                    }
+                }
+            }
+            _ => {
+                if let Some(character_id) = event.character_id() {
+                    self.refresh(character_id, world_id, timestamp, true);
+                }
+                if let Some(character_id) = event.other_character_id() {
+                    self.refresh(character_id, world_id, timestamp, false);
+                }
+            }
+        }
+    }
+
+    fn refresh(
+        &mut self,
+        character_id: CharacterId,
+        world_id: WorldId,
+        timestamp: Timestamp,
+        insert: bool,
+    ) {
+        match self.characters.entry(character_id) {
+            Entry::Occupied(mut entry) => {
+                let presence = entry.get_mut();
+                if presence.world_id != Some(world_id) {
+                    if presence.world_id.is_some() {
+                        // The logout on the previous world got lost.
+                        presence.login = None;
+                    }
+                    presence.world_id = Some(world_id);
+                }
+                presence.last_seen = presence.last_seen.max(timestamp);
+            }
+            Entry::Vacant(entry) => {
+                if insert {
+                    entry.insert(Presence {
+                        world_id: Some(world_id),
+                        login: None,
+                        last_seen: timestamp,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Removes the characters not seen for longer than the timeout before `now`, returning how
+    /// many were removed.
+    pub fn expire(&mut self, now: Timestamp) -> usize {
+        let timeout = self.timeout;
+        let before = self.characters.len();
+        self.characters
+            .retain(|_, presence| now.saturating_duration_since(presence.last_seen) <= timeout);
+
+        before - self.characters.len()
+    }
+
+    /// The number of online characters on a world.
+    pub fn count(&self, world_id: WorldId) -> usize {
+        self.characters
+            .values()
+            .filter(|presence| presence.world_id == Some(world_id))
+            .count()
+    }
+
+    /// The number of online characters on all worlds, including seeded ones of unknown world.
+    pub fn total(&self) -> usize {
+        self.characters.len()
+    }
+
+    /// The number of online characters per world, excluding seeded ones of unknown world.
+    pub fn counts(&self) -> HashMap<WorldId, usize> {
+        let mut counts = HashMap::new();
+        for world_id in self.characters.values().filter_map(|presence| presence.world_id) {
+            *counts.entry(world_id).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    /// The online characters of a world.
+    pub fn online(&self, world_id: WorldId) -> impl Iterator<Item = (CharacterId, &Presence)> {
+        self.characters
+            .iter()
+            .filter(move |&(_, presence)| presence.world_id == Some(world_id))
+            .map(|(&character_id, presence)| (character_id, presence))
+    }
+
+    pub fn presence(&self, character_id: CharacterId) -> Option<&Presence> {
+        self.characters.get(&character_id)
+    }
+
+    pub fn is_online(&self, character_id: CharacterId) -> bool {
+        self.characters.contains_key(&character_id)
+    }
+
+    /// When an online character logged in, if known.
+    pub fn login_time(&self, character_id: CharacterId) -> Option<Timestamp> {
+        self.characters
+            .get(&character_id)
+            .and_then(|presence| presence.login)
+    }
+}
+
+impl EventHandler for PopulationTracker {
+    fn on_event(&mut self, event: &Event) {
+        self.handle_event(event)
+    }
+
+    /// Seeds as of the latest event seen, or now before any events.
+    fn on_recent_character_ids(&mut self, character_ids: &[CharacterId]) {
+        let now = self.latest.unwrap_or_else(Timestamp::now);
+        self.seed(character_ids.iter().cloned(), now);
+    }
+
+    fn on_heartbeat(&mut self, _online: &HashMap<String, String>) {
+        if let Some(latest) = self.latest {
+            self.expire(latest);
+        }
+    }
+}
+
+/// The number of characters per faction.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct FactionCounts {
+    pub vs: usize,
+    pub nc: usize,
+    pub tr: usize,
+    pub nso: usize,
+}
+
+impl FactionCounts {
+    pub fn get(&self, faction: Faction) -> usize {
+        match faction {
+            Faction::Vs => self.vs,
+            Faction::Nc => self.nc,
+            Faction::Tr => self.tr,
+            Faction::Nso => self.nso,
+        }
+    }
+
+    fn get_mut(&mut self, faction: Faction) -> &mut usize {
+        match faction {
+            Faction::Vs => &mut self.vs,
+            Faction::Nc => &mut self.nc,
+            Faction::Tr => &mut self.tr,
+            Faction::Nso => &mut self.nso,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.vs + self.nc + self.tr + self.nso
+    }
+
+    /// The share of a faction in percent, `0.0` if there are no characters at all.
+    pub fn percentage(&self, faction: Faction) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.get(faction) as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Activity {
+    world_id: WorldId,
+    zone_id: ZoneId,
+    faction: Faction,
+    last_seen: Timestamp,
+}
+
+/// Estimates faction populations from the characters active within a sliding window.
+///
+/// Login events carry no faction, but the loadouts of `Death` and `GainExperience` events do. Each
+/// character counts once, for the world, zone and faction of its latest event. NSO characters
+/// count as NSO, since their loadouts do not tell which empire they currently fight for.
+///
+/// The window ends at the latest event seen, so replays estimate like live streams do.
+#[derive(Clone, Debug)]
+pub struct FactionPopulation {
+    window: Duration,
+    characters: HashMap<CharacterId, Activity>,
+    latest: Option<Timestamp>,
+}
+
+impl FactionPopulation {
+    pub fn new(window: Duration) -> FactionPopulation {
+        FactionPopulation {
+            window,
+            characters: HashMap::new(),
+            latest: None,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match *event {
+            Event::Death(ref death) => {
+                self.saw(
+                    death.character_id,
+                    death.character_loadout_id,
+                    death.world_id,
+                    death.zone_id,
+                    death.timestamp,
+                );
+                self.saw(
+                    death.attacker_character_id,
+                    death.attacker_loadout_id,
+                    death.world_id,
+                    death.zone_id,
+                    death.timestamp,
+                );
+            }
+            Event::GainExperience(ref gain_experience) => self.saw(
+                gain_experience.character_id,
+                gain_experience.loadout_id,
+                gain_experience.world_id,
+                gain_experience.zone_id,
+                gain_experience.timestamp,
+            ),
+            _ => {}
+        }
+    }
+
+    fn saw(
+        &mut self,
+        character_id: CharacterId,
+        loadout_id: LoadoutId,
+        world_id: WorldId,
+        zone_id: ZoneId,
+        timestamp: Timestamp,
+    ) {
+        if self.latest.map_or(true, |latest| latest < timestamp) {
+            self.latest = Some(timestamp);
+        }
+
+        let faction = match Loadout::try_from(loadout_id) {
+            Ok(loadout) => loadout.faction,
+            Err(_) => return,
+        };
+        if character_id.get() == 0 {
+            return;
+        }
+
+        let activity = Activity {
+            world_id,
+            zone_id,
+            faction,
+            last_seen: timestamp,
+        };
+        match self.characters.entry(character_id) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().last_seen <= timestamp {
+                    entry.insert(activity);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(activity);
+            }
+        }
+    }
+
+    /// Forgets the characters that are no longer within the window.
+    pub fn prune(&mut self) {
+        if let Some(latest) = self.latest {
+            let window = self.window;
+            self.characters.retain(|_, activity| {
+                latest.saturating_duration_since(activity.last_seen) <= window
+            });
+        }
+    }
+
+    fn active(&self) -> impl Iterator<Item = &Activity> {
+        let latest = self.latest;
+        let window = self.window;
+        self.characters.values().filter(move |activity| {
+            latest.map_or(false, |latest| {
+                latest.saturating_duration_since(activity.last_seen) <= window
+            })
+        })
+    }
+
+    /// The active characters of a world.
+    pub fn world(&self, world_id: WorldId) -> FactionCounts {
+        let mut counts = FactionCounts::default();
+        for activity in self.active().filter(|activity| activity.world_id == world_id) {
+            *counts.get_mut(activity.faction) += 1;
+        }
+
+        counts
+    }
+
+    /// The active characters of a zone instance on a world.
+    pub fn zone(&self, world_id: WorldId, zone_id: ZoneId) -> FactionCounts {
+        let mut counts = FactionCounts::default();
+        for activity in self.active()
+            .filter(|activity| activity.world_id == world_id && activity.zone_id == zone_id)
+        {
+            *counts.get_mut(activity.faction) += 1;
+        }
+
+        counts
+    }
+
+    /// The active characters of each zone of a world.
+    pub fn zones(&self, world_id: WorldId) -> HashMap<ZoneId, FactionCounts> {
+        let mut zones = HashMap::new();
+        for activity in self.active().filter(|activity| activity.world_id == world_id) {
+            let counts: &mut FactionCounts = zones.entry(activity.zone_id).or_default();
+            *counts.get_mut(activity.faction) += 1;
+        }
+
+        zones
+    }
+
+    /// The active characters of each continent of a world, e.g. for faction percentages with
+    /// `FactionCounts::percentage`.
+    pub fn continents(&self, world_id: WorldId) -> HashMap<ZoneId, FactionCounts> {
+        let mut zones = self.zones(world_id);
+        zones.retain(|zone_id, _| zone_id.zone().map_or(false, |zone| zone.is_continent()));
+
+        zones
+    }
+}
+
+impl EventHandler for FactionPopulation {
+    fn on_event(&mut self, event: &Event) {
+        self.handle_event(event)
+    }
+
+    fn on_heartbeat(&mut self, _online: &HashMap<String, String>) {
+        self.prune()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use fixtures::{login, logout, skill_added};
+    use response::{Death, GainExperience};
+    use zone::Zone;
+    use {ExperienceId, ItemId, VehicleId};
+
+    #[test]
+    fn login_logout() {
+        let mut tracker = PopulationTracker::new(Duration::from_secs(600));
+        tracker.handle_event(&login(1, 13, 100));
+        tracker.handle_event(&login(2, 13, 110));
+        tracker.handle_event(&login(3, 17, 120));
+        tracker.handle_event(&login(1, 13, 130));
+
+        assert_eq!(tracker.count(WorldId::new(13)), 2);
+        assert_eq!(tracker.count(WorldId::new(17)), 1);
+        assert_eq!(tracker.login_time(CharacterId::new(1)), Some(Timestamp::from_secs(100)));
+
+        tracker.handle_event(&logout(2, 13, 140));
+        assert!(!tracker.is_online(CharacterId::new(2)));
+
+        tracker.handle_event(&logout(1, 13, 120));
+        assert!(tracker.is_online(CharacterId::new(1)));
+        assert_eq!(tracker.counts().get(&WorldId::new(13)), Some(&1));
+        assert_eq!(
+            tracker.online(WorldId::new(17)).map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![CharacterId::new(3)]
+        );
+    }
+
+    #[test]
+    fn seed_from_reply() {
+        let reply = serde_json::from_value(json!({
+            "recent_character_id_list": ["5428010618015189713", "5428713425545165425"],
+            "service": "event",
+            "type": "serviceMessage"
+        })).unwrap();
+
+        let mut tracker = PopulationTracker::new(Duration::from_secs(600));
+        assert!(tracker.seed_from(&reply, Timestamp::from_secs(100)));
+        assert_eq!(tracker.total(), 2);
+        assert!(tracker.is_online(CharacterId::new(5428713425545165425)));
+
+        let heartbeat = Message::Heartbeat {
+            online: HashMap::new(),
+        };
+        assert!(!tracker.seed_from(&heartbeat, Timestamp::from_secs(100)));
+
+        let mut tracker = PopulationTracker::new(Duration::from_secs(600));
+        tracker.handle_event(&login(1, 13, 100));
+        tracker.on_message(&reply);
+        assert_eq!(tracker.total(), 3);
+        assert_eq!(
+            tracker.presence(CharacterId::new(5428010618015189713)).unwrap().last_seen,
+            Timestamp::from_secs(100)
+        );
+    }
+
+    #[test]
+    fn seed_and_expire() {
+        let mut tracker = PopulationTracker::new(Duration::from_secs(600));
+        tracker.seed(
+            vec![CharacterId::new(1), CharacterId::new(2)],
+            Timestamp::from_secs(100),
+        );
+        assert_eq!(tracker.total(), 2);
+        assert_eq!(tracker.count(WorldId::new(13)), 0);
+
+        tracker.handle_event(&skill_added(1, 13, 500));
+        assert_eq!(tracker.count(WorldId::new(13)), 1);
+        assert_eq!(tracker.login_time(CharacterId::new(1)), None);
+
+        assert_eq!(tracker.expire(Timestamp::from_secs(800)), 1);
+        assert!(tracker.is_online(CharacterId::new(1)));
+        assert!(!tracker.is_online(CharacterId::new(2)));
+    }
+
+    fn death(attacker: (u64, u64), victim: (u64, u64), zone: Zone, timestamp: u64) -> Event {
+        Event::Death(Death {
+            attacker_character_id: CharacterId::new(attacker.0),
+            attacker_fire_mode_id: 0,
+            attacker_loadout_id: LoadoutId::new(attacker.1),
+            attacker_team_id: None,
+            attacker_vehicle_id: VehicleId::new(0),
+            attacker_weapon_id: ItemId::new(0),
+            character_id: CharacterId::new(victim.0),
+            character_loadout_id: LoadoutId::new(victim.1),
+            is_critical: false,
+            is_headshot: false,
+            team_id: None,
+            timestamp: Timestamp::from_secs(timestamp),
+            vehicle_id: VehicleId::new(0),
+            world_id: WorldId::new(13),
+            zone_id: ZoneId::from(zone),
+        })
+    }
+
+    fn gain_experience(character: (u64, u64), zone_id: ZoneId, timestamp: u64) -> Event {
+        Event::GainExperience(GainExperience {
+            amount: 100,
+            character_id: CharacterId::new(character.0),
+            experience_id: ExperienceId::new(4),
+            loadout_id: LoadoutId::new(character.1),
+            other_id: 0,
+            team_id: None,
+            timestamp: Timestamp::from_secs(timestamp),
+            world_id: WorldId::new(13),
+            zone_id,
+        })
+    }
+
+    #[test]
+    fn faction_population() {
+        let mut population = FactionPopulation::new(Duration::from_secs(200));
+        population.handle_event(&death((1, 15), (2, 1), Zone::Indar, 100));
+        population.handle_event(&death((3, 8), (4, 28), Zone::Indar, 200));
+        population.handle_event(&gain_experience((5, 17), ZoneId::from(Zone::Esamir), 300));
+        population.handle_event(&gain_experience((6, 3), ZoneId::from_parts(361, 3), 300));
+        population.handle_event(&gain_experience((1, 15), ZoneId::from(Zone::Indar), 350));
+
+        let world = population.world(WorldId::new(13));
+        assert_eq!(
+            world,
+            FactionCounts {
+                vs: 2,
+                nc: 1,
+                tr: 1,
+                nso: 1,
+            }
+        );
+        assert_eq!(world.total(), 5);
+        assert_eq!(world.percentage(Faction::Vs), 40.0);
+
+        let continents = population.continents(WorldId::new(13));
+        assert_eq!(continents.len(), 2);
+        assert_eq!(continents[&ZoneId::from(Zone::Indar)].total(), 3);
+        assert_eq!(continents[&ZoneId::from(Zone::Esamir)].vs, 1);
+        assert_eq!(population.zone(WorldId::new(13), ZoneId::from_parts(361, 3)).nc, 1);
+
+        population.prune();
+        assert_eq!(population.world(WorldId::new(13)).total(), 5);
+        assert_eq!(population.world(WorldId::new(1)).total(), 0);
+    }
+}
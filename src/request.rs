@@ -1,34 +1,67 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
 use serde;
+use serde::de::IntoDeserializer;
 use serde_json;
 
-use {CharacterId, ExperienceId, Id, Service, WorldId};
+use world::World;
+use {CharacterId, ExperienceId, Service, WorldId};
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
 #[serde(untagged)]
 pub enum CharacterSubscription {
     #[serde(serialize_with = "serialize_all_subscription")] All,
     #[serde(serialize_with = "serialize_ids_subscription")] Ids(Vec<CharacterId>),
 }
 
-#[repr(u64)]
-pub enum WorldIds {
-    Jaeger = 19,
-    Briggs = 25,
-    Miller = 10,
-    Cobalt = 13,
-    Connery = 1,
-    Emerald = 17,
-}
+#[deprecated(note = "use `world::World` instead")]
+pub type WorldIds = World;
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
 #[serde(untagged)]
 pub enum WorldSubscription {
     #[serde(serialize_with = "serialize_all_subscription")] All,
-    // TODO: WorldIds enum instead of WorldId u64?
     #[serde(serialize_with = "serialize_ids_subscription")] Ids(Vec<WorldId>),
 }
 
-#[derive(PartialEq, Eq, Hash)]
+impl WorldSubscription {
+    pub fn worlds<I>(worlds: I) -> WorldSubscription
+    where
+        I: IntoIterator<Item = World>,
+    {
+        WorldSubscription::Ids(worlds.into_iter().map(WorldId::from).collect())
+    }
+
+    pub fn contains(&self, world_id: WorldId) -> bool {
+        match *self {
+            WorldSubscription::All => true,
+            WorldSubscription::Ids(ref ids) => ids.contains(&world_id),
+        }
+    }
+
+    /// Whether every world of `other` is also in this subscription.
+    pub fn includes(&self, other: &WorldSubscription) -> bool {
+        match *other {
+            WorldSubscription::All => *self == WorldSubscription::All,
+            WorldSubscription::Ids(ref ids) => ids.iter().all(|&id| self.contains(id)),
+        }
+    }
+
+    /// Whether no world of `other` is in this subscription.
+    pub fn excludes(&self, other: &WorldSubscription) -> bool {
+        match *self {
+            WorldSubscription::Ids(ref ids) if ids.is_empty() => true,
+            _ => match *other {
+                WorldSubscription::All => false,
+                WorldSubscription::Ids(ref ids) => ids.iter().all(|&id| !self.contains(id)),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum EventNames {
     AchievementEarned,
     BattleRankUp,
@@ -48,14 +81,50 @@ pub enum EventNames {
     PlayerLogout,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
 #[serde(untagged)]
 pub enum EventSubscription {
     #[serde(serialize_with = "serialize_all_subscription")] All,
     Ids(Vec<EventNames>),
 }
 
-#[derive(Serialize)]
+impl EventSubscription {
+    /// Whether events named `event_name` are subscribed to.
+    ///
+    /// `GainExperience` includes every `GainExperienceId`.
+    pub fn contains(&self, event_name: EventNames) -> bool {
+        match *self {
+            EventSubscription::All => true,
+            EventSubscription::Ids(ref ids) => {
+                ids.contains(&event_name) || match event_name {
+                    EventNames::GainExperienceId(_) => ids.contains(&EventNames::GainExperience),
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// Whether every event name of `other` is also in this subscription.
+    pub fn includes(&self, other: &EventSubscription) -> bool {
+        match *other {
+            EventSubscription::All => *self == EventSubscription::All,
+            EventSubscription::Ids(ref ids) => ids.iter().all(|&id| self.contains(id)),
+        }
+    }
+
+    /// Whether no event name of `other` is in this subscription.
+    pub fn excludes(&self, other: &EventSubscription) -> bool {
+        match *self {
+            EventSubscription::Ids(ref ids) if ids.is_empty() => true,
+            _ => match *other {
+                EventSubscription::All => false,
+                EventSubscription::Ids(ref ids) => ids.iter().all(|&id| !self.contains(id)),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 #[serde(tag = "action", rename_all = "camelCase")]
 pub enum Action {
     Echo {
@@ -73,8 +142,9 @@ pub enum Action {
     },
     #[serde(rename_all = "camelCase")]
     ClearSubscribe {
-        #[serde(skip_serializing_if = "Option::is_none",
-                serialize_with = "serialize_optional_bool")]
+        #[serde(default, skip_serializing_if = "Option::is_none",
+                serialize_with = "serialize_optional_bool",
+                deserialize_with = "deserialize_optional_bool")]
         all: Option<bool>,
         #[serde(skip_serializing_if = "Option::is_none")] event_names: Option<EventSubscription>,
         #[serde(skip_serializing_if = "Option::is_none")] characters: Option<CharacterSubscription>,
@@ -102,6 +172,52 @@ where
     }
 }
 
+/// Deserializes a bool, either string-encoded or native.
+fn deserialize_optional_bool<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct BoolVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for BoolVisitor {
+        type Value = Option<bool>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a bool or \"true\" or \"false\"")
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<Option<bool>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(Some(value))
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Option<bool>, E>
+        where
+            E: serde::de::Error,
+        {
+            value.parse().map(Some).map_err(E::custom)
+        }
+
+        fn visit_none<E>(self) -> Result<Option<bool>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Option<bool>, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(None)
+        }
+    }
+
+    deserializer.deserialize_any(BoolVisitor)
+}
+
 fn serialize_all_subscription<S>(serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -111,64 +227,302 @@ where
     json!(["all"]).serialize(serializer)
 }
 
-fn serialize_ids_subscription<S>(value: &Vec<Id>, serializer: S) -> Result<S::Ok, S::Error>
+fn serialize_ids_subscription<T, S>(value: &[T], serializer: S) -> Result<S::Ok, S::Error>
 where
+    T: serde::Serialize,
     S: serde::Serializer,
 {
-    let mut ids = Vec::with_capacity(value.len());
-    for id in value.iter() {
-        ids.push(id.to_string());
+    serializer.collect_seq(value.iter())
+}
+
+/// Deserializes either `["all"]` as `None` or a list of ids.
+fn deserialize_subscription<'de, T, D>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    enum AllOr<T> {
+        All,
+        Id(T),
+    }
+
+    impl<'de, T> serde::Deserialize<'de> for AllOr<T>
+    where
+        T: serde::Deserialize<'de>,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<AllOr<T>, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            struct AllOrVisitor<T>(PhantomData<T>);
+
+            impl<'de, T> serde::de::Visitor<'de> for AllOrVisitor<T>
+            where
+                T: serde::Deserialize<'de>,
+            {
+                type Value = AllOr<T>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("\"all\" or an id")
+                }
+
+                fn visit_u64<E>(self, value: u64) -> Result<AllOr<T>, E>
+                where
+                    E: serde::de::Error,
+                {
+                    T::deserialize(value.into_deserializer()).map(AllOr::Id)
+                }
+
+                fn visit_str<E>(self, value: &str) -> Result<AllOr<T>, E>
+                where
+                    E: serde::de::Error,
+                {
+                    match value {
+                        "all" => Ok(AllOr::All),
+                        value => T::deserialize(value.into_deserializer()).map(AllOr::Id),
+                    }
+                }
+            }
+
+            deserializer.deserialize_any(AllOrVisitor(PhantomData))
+        }
     }
 
-    serializer.collect_seq(ids.iter())
+    let items = Vec::<AllOr<T>>::deserialize(deserializer)?;
+    if items.iter().any(|item| match *item {
+        AllOr::All => true,
+        AllOr::Id(_) => false,
+    }) {
+        return if items.len() == 1 {
+            Ok(None)
+        } else {
+            Err(serde::de::Error::custom("\"all\" must not be combined with ids"))
+        };
+    }
+
+    Ok(Some(
+        items
+            .into_iter()
+            .filter_map(|item| match item {
+                AllOr::All => None,
+                AllOr::Id(id) => Some(id),
+            })
+            .collect(),
+    ))
 }
 
-impl serde::Serialize for EventNames {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+impl<'de> serde::Deserialize<'de> for CharacterSubscription {
+    fn deserialize<D>(deserializer: D) -> Result<CharacterSubscription, D::Error>
     where
-        S: serde::Serializer,
+        D: serde::Deserializer<'de>,
+    {
+        Ok(match deserialize_subscription(deserializer)? {
+            None => CharacterSubscription::All,
+            Some(ids) => CharacterSubscription::Ids(ids),
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for WorldSubscription {
+    fn deserialize<D>(deserializer: D) -> Result<WorldSubscription, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(match deserialize_subscription(deserializer)? {
+            None => WorldSubscription::All,
+            Some(ids) => WorldSubscription::Ids(ids),
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for EventSubscription {
+    fn deserialize<D>(deserializer: D) -> Result<EventSubscription, D::Error>
+    where
+        D: serde::Deserializer<'de>,
     {
+        Ok(match deserialize_subscription(deserializer)? {
+            None => EventSubscription::All,
+            Some(ids) => EventSubscription::Ids(ids),
+        })
+    }
+}
+
+/// Whether an event is about a character or about a world.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EventScope {
+    /// Events of a character, which are subscribed to via `characters`.
+    ///
+    /// `PlayerLogin` and `PlayerLogout` are also sent for subscribed `worlds`.
+    Character,
+    /// Events of a world, which are subscribed to via `worlds`.
+    World,
+}
+
+impl EventNames {
+    const GAIN_EXPERIENCE_ID_PREFIX: &'static str = "GainExperience_experience_id_";
+
+    /// All event names, except for the parameterized `GainExperienceId`.
+    pub const ALL: [EventNames; 15] = [
+        EventNames::AchievementEarned,
+        EventNames::BattleRankUp,
+        EventNames::Death,
+        EventNames::ItemAdded,
+        EventNames::SkillAdded,
+        EventNames::VehicleDestroy,
+        EventNames::GainExperience,
+        EventNames::PlayerFacilityCapture,
+        EventNames::PlayerFacilityDefend,
+        EventNames::ContinentLock,
+        EventNames::ContinentUnlock,
+        EventNames::FacilityControl,
+        EventNames::MetagameEvent,
+        EventNames::PlayerLogin,
+        EventNames::PlayerLogout,
+    ];
+
+    /// Iterates over `EventNames::ALL`.
+    pub fn all() -> impl Iterator<Item = EventNames> {
+        EventNames::ALL.iter().cloned()
+    }
+
+    /// The name, unless this is a `GainExperienceId`, whose name includes the id.
+    pub fn as_str(&self) -> Option<&'static str> {
+        use self::EventNames::*;
+
+        let name = match *self {
+            AchievementEarned => "AchievementEarned",
+            BattleRankUp => "BattleRankUp",
+            Death => "Death",
+            ItemAdded => "ItemAdded",
+            SkillAdded => "SkillAdded",
+            VehicleDestroy => "VehicleDestroy",
+            GainExperience => "GainExperience",
+            GainExperienceId(_) => return None,
+            PlayerFacilityCapture => "PlayerFacilityCapture",
+            PlayerFacilityDefend => "PlayerFacilityDefend",
+            ContinentLock => "ContinentLock",
+            ContinentUnlock => "ContinentUnlock",
+            FacilityControl => "FacilityControl",
+            MetagameEvent => "MetagameEvent",
+            PlayerLogin => "PlayerLogin",
+            PlayerLogout => "PlayerLogout",
+        };
+
+        Some(name)
+    }
+
+    pub fn scope(&self) -> EventScope {
         use self::EventNames::*;
 
         match *self {
-            AchievementEarned => {
-                serializer.serialize_unit_variant("EventNames", 0, "AchievementEarned")
-            }
-            BattleRankUp => serializer.serialize_unit_variant("EventNames", 1, "BattleRankUp"),
-            Death => serializer.serialize_unit_variant("EventNames", 2, "Death"),
-            ItemAdded => serializer.serialize_unit_variant("EventNames", 3, "ItemAdded"),
-            SkillAdded => serializer.serialize_unit_variant("EventNames", 4, "SkillAdded"),
-            VehicleDestroy => serializer.serialize_unit_variant("EventNames", 5, "VehicleDestroy"),
-            GainExperience => serializer.serialize_unit_variant("EventNames", 6, "GainExperience"),
-            GainExperienceId(value) => {
-                let event_name = format!("GainExperience_experience_id_{}", value);
-
-                serializer.serialize_str(&event_name)
-            }
-            PlayerFacilityCapture => {
-                serializer.serialize_unit_variant("EventNames", 8, "PlayerFacilityCapture")
-            }
-            PlayerFacilityDefend => {
-                serializer.serialize_unit_variant("EventNames", 9, "PlayerFacilityDefend")
+            AchievementEarned
+            | BattleRankUp
+            | Death
+            | ItemAdded
+            | SkillAdded
+            | VehicleDestroy
+            | GainExperience
+            | GainExperienceId(_)
+            | PlayerFacilityCapture
+            | PlayerFacilityDefend
+            | PlayerLogin
+            | PlayerLogout => EventScope::Character,
+            ContinentLock | ContinentUnlock | FacilityControl | MetagameEvent => EventScope::World,
+        }
+    }
+
+    pub fn is_character_event(&self) -> bool {
+        self.scope() == EventScope::Character
+    }
+
+    pub fn is_world_event(&self) -> bool {
+        self.scope() == EventScope::World
+    }
+}
+
+impl fmt::Display for EventNames {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EventNames::GainExperienceId(id) => {
+                write!(f, "{}{}", EventNames::GAIN_EXPERIENCE_ID_PREFIX, id)
             }
-            ContinentLock => serializer.serialize_unit_variant("EventNames", 10, "ContinentLock"),
-            ContinentUnlock => {
-                serializer.serialize_unit_variant("EventNames", 11, "ContinentUnlock")
+            ref event_name => f.write_str(event_name.as_str().expect("not parameterized")),
+        }
+    }
+}
+
+impl FromStr for EventNames {
+    type Err = ParseEventNamesError;
+
+    fn from_str(s: &str) -> Result<EventNames, ParseEventNamesError> {
+        if let Some(id) = s.strip_prefix(EventNames::GAIN_EXPERIENCE_ID_PREFIX) {
+            return id
+                .parse()
+                .map(EventNames::GainExperienceId)
+                .map_err(|_| ParseEventNamesError(()));
+        }
+
+        EventNames::all()
+            .find(|event_name| event_name.as_str() == Some(s))
+            .ok_or(ParseEventNamesError(()))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for EventNames {
+    fn deserialize<D>(deserializer: D) -> Result<EventNames, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct EventNamesVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for EventNamesVisitor {
+            type Value = EventNames;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an event name")
             }
-            FacilityControl => {
-                serializer.serialize_unit_variant("EventNames", 12, "FacilityControl")
+
+            fn visit_str<E>(self, value: &str) -> Result<EventNames, E>
+            where
+                E: serde::de::Error,
+            {
+                value
+                    .parse()
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(value), &self))
             }
-            MetagameEvent => serializer.serialize_unit_variant("EventNames", 13, "MetagameEvent"),
-            PlayerLogin => serializer.serialize_unit_variant("EventNames", 14, "PlayerLogin"),
-            PlayerLogout => serializer.serialize_unit_variant("EventNames", 15, "PlayerLogout"),
         }
+
+        deserializer.deserialize_str(EventNamesVisitor)
     }
 }
 
+impl serde::Serialize for EventNames {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEventNamesError(());
+
+impl fmt::Display for ParseEventNamesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unknown event name")
+    }
+}
+
+impl ::std::error::Error for ParseEventNamesError {}
+
 #[cfg(test)]
 // TODO: Replace `assert_eq!(.., json!())` with https://docs.serde.rs/serde_test/
 mod tests {
     use super::*;
+    use experience::{Experience, ExperienceCategory};
 
     #[test]
     fn serialize_echo_action() {
@@ -195,8 +549,8 @@ mod tests {
     fn serialize_subscribe_gainexperienceid_event_action() {
         let input = Action::Subscribe {
             event_names: Some(EventSubscription::Ids(vec![
-                EventNames::GainExperienceId(4),
-                EventNames::GainExperienceId(5),
+                EventNames::GainExperienceId(ExperienceId::new(4)),
+                EventNames::GainExperienceId(ExperienceId::new(5)),
             ])),
             characters: Some(CharacterSubscription::All),
             logical_and_characters_with_worlds: None,
@@ -218,11 +572,39 @@ mod tests {
         assert_eq!(v, expected);
     }
 
+    #[test]
+    fn serialize_subscribe_experience_event_action() {
+        let input = Action::Subscribe {
+            event_names: Some(EventSubscription::Ids(
+                Experience::of_category(ExperienceCategory::Objective)
+                    .map(EventNames::from)
+                    .collect(),
+            )),
+            characters: Some(CharacterSubscription::All),
+            logical_and_characters_with_worlds: None,
+            worlds: None,
+            service: Service::Event,
+        };
+        let v = serde_json::to_value(input).unwrap();
+
+        let expected = json!({
+            "service": "event",
+            "action": "subscribe",
+            "eventNames": [
+                "GainExperience_experience_id_15",
+                "GainExperience_experience_id_16"
+            ],
+            "characters": [ "all" ]
+        });
+
+        assert_eq!(v, expected);
+    }
+
     #[test]
     fn serialize_subscribe_character_death_action() {
         let input = Action::Subscribe {
             event_names: Some(EventSubscription::Ids(vec![EventNames::Death])),
-            characters: Some(CharacterSubscription::Ids(vec![5428010618015189713])),
+            characters: Some(CharacterSubscription::Ids(vec![CharacterId::new(5428010618015189713)])),
             logical_and_characters_with_worlds: None,
             worlds: None,
             service: Service::Event,
@@ -245,7 +627,7 @@ mod tests {
             event_names: Some(EventSubscription::Ids(vec![EventNames::PlayerLogin])),
             characters: None,
             logical_and_characters_with_worlds: None,
-            worlds: Some(WorldSubscription::Ids(vec![WorldIds::Connery as WorldId])),
+            worlds: Some(WorldSubscription::worlds(vec![World::Connery])),
             service: Service::Event,
         };
         let v = serde_json::to_value(input).unwrap();
@@ -301,10 +683,7 @@ mod tests {
             ])),
             characters: Some(CharacterSubscription::All),
             logical_and_characters_with_worlds: Some(true),
-            worlds: Some(WorldSubscription::Ids(vec![
-                WorldIds::Cobalt as WorldId,
-                WorldIds::Jaeger as WorldId,
-            ])),
+            worlds: Some(WorldSubscription::worlds(vec![World::Cobalt, World::Jaeger])),
             service: Service::Event,
         };
         let v = serde_json::to_value(input).unwrap();
@@ -355,11 +734,8 @@ mod tests {
                 EventNames::Death,
                 EventNames::PlayerLogout,
             ])),
-            characters: Some(CharacterSubscription::Ids(vec![1, 2])),
-            worlds: Some(WorldSubscription::Ids(vec![
-                WorldIds::Cobalt as WorldId,
-                WorldIds::Jaeger as WorldId,
-            ])),
+            characters: Some(CharacterSubscription::Ids(vec![CharacterId::new(1), CharacterId::new(2)])),
+            worlds: Some(WorldSubscription::worlds(vec![World::Cobalt, World::Jaeger])),
             service: Service::Event,
         };
         let v = serde_json::to_value(input).unwrap();
@@ -414,6 +790,164 @@ mod tests {
         assert_eq!(v, expected);
     }
 
+    #[test]
+    fn deserialize_subscribe_action() {
+        let input = json!({
+            "service": "event",
+            "action": "subscribe",
+            "eventNames": [
+                "PlayerLogin",
+                "GainExperience_experience_id_7"
+            ],
+            "characters": [ "all" ],
+            "logicalAndCharactersWithWorlds": true,
+            "worlds": [
+                "13",
+                "19"
+            ]
+        });
+        let action: Action = serde_json::from_value(input.clone()).unwrap();
+
+        let expected = Action::Subscribe {
+            event_names: Some(EventSubscription::Ids(vec![
+                EventNames::PlayerLogin,
+                EventNames::GainExperienceId(ExperienceId::new(7)),
+            ])),
+            characters: Some(CharacterSubscription::All),
+            logical_and_characters_with_worlds: Some(true),
+            worlds: Some(WorldSubscription::worlds(vec![World::Cobalt, World::Jaeger])),
+            service: Service::Event,
+        };
+
+        assert_eq!(action, expected);
+        assert_eq!(serde_json::to_value(action).unwrap(), input);
+    }
+
+    #[test]
+    fn deserialize_clearsubscribe_action() {
+        let input = json!({
+            "service": "event",
+            "action": "clearSubscribe",
+            "all": "true"
+        });
+        let action: Action = serde_json::from_value(input.clone()).unwrap();
+
+        let expected = Action::ClearSubscribe {
+            all: Some(true),
+            event_names: None,
+            characters: None,
+            worlds: None,
+            service: Service::Event,
+        };
+
+        assert_eq!(action, expected);
+        assert_eq!(serde_json::to_value(action).unwrap(), input);
+
+        let input = json!({
+            "service": "event",
+            "action": "clearSubscribe",
+            "characters": [ "1", "2" ]
+        });
+        let action: Action = serde_json::from_value(input.clone()).unwrap();
+
+        let expected = Action::ClearSubscribe {
+            all: None,
+            event_names: None,
+            characters: Some(CharacterSubscription::Ids(vec![
+                CharacterId::new(1),
+                CharacterId::new(2),
+            ])),
+            worlds: None,
+            service: Service::Event,
+        };
+
+        assert_eq!(action, expected);
+        assert_eq!(serde_json::to_value(action).unwrap(), input);
+    }
+
+    #[test]
+    fn deserialize_echo_action() {
+        let input = json!({
+            "service": "push",
+            "action": "echo",
+            "payload": {
+                "test": "test"
+            }
+        });
+        let action: Action = serde_json::from_value(input.clone()).unwrap();
+
+        let expected = Action::Echo {
+            payload: json!({
+                "test": "test"
+            }),
+            service: Service::Push,
+        };
+
+        assert_eq!(action, expected);
+        assert_eq!(serde_json::to_value(action).unwrap(), input);
+    }
+
+    #[test]
+    fn deserialize_recentcharacterids_action() {
+        let action: Action = serde_json::from_value(json!({
+            "service": "event",
+            "action": "recentCharacterIdsCount"
+        })).unwrap();
+
+        assert_eq!(
+            action,
+            Action::RecentCharacterIdsCount {
+                service: Service::Event,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_subscriptions() {
+        assert!(serde_json::from_value::<WorldSubscription>(json!(["all", "1"])).is_err());
+        assert!(serde_json::from_value::<WorldSubscription>(json!(["Cobalt"])).is_err());
+        assert!(serde_json::from_value::<EventSubscription>(json!(["Fishing"])).is_err());
+        assert!(
+            serde_json::from_value::<EventSubscription>(json!([
+                "GainExperience_experience_id_"
+            ])).is_err()
+        );
+    }
+
+    #[test]
+    fn event_names_fromstr_display() {
+        for event_name in EventNames::all() {
+            assert_eq!(event_name.to_string().parse(), Ok(event_name));
+        }
+
+        let event_name = EventNames::GainExperienceId(ExperienceId::new(7));
+        assert_eq!(event_name.to_string(), "GainExperience_experience_id_7");
+        assert_eq!("GainExperience_experience_id_7".parse(), Ok(event_name));
+
+        assert!("GainExperience_experience_id_".parse::<EventNames>().is_err());
+        assert!("GainExperience_experience_id_x".parse::<EventNames>().is_err());
+        assert!("Fishing".parse::<EventNames>().is_err());
+    }
+
+    #[test]
+    fn event_names_scope() {
+        assert_eq!(EventNames::Death.scope(), EventScope::Character);
+        assert!(EventNames::GainExperienceId(ExperienceId::new(7)).is_character_event());
+        assert!(EventNames::PlayerLogin.is_character_event());
+        assert!(EventNames::MetagameEvent.is_world_event());
+
+        let world_events: Vec<_> = EventNames::all().filter(EventNames::is_world_event).collect();
+        assert_eq!(
+            world_events,
+            vec![
+                EventNames::ContinentLock,
+                EventNames::ContinentUnlock,
+                EventNames::FacilityControl,
+                EventNames::MetagameEvent,
+            ]
+        );
+    }
+
     #[test]
     fn serialize_recentcharacterids_action() {
         let input = Action::RecentCharacterIds {
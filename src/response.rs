@@ -1,36 +1,610 @@
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std;
 use serde;
-use serde_json;
-use {CharacterId, Timestamp, WorldId};
+use experience::Experience;
+use faction::Faction;
+use loadout::Loadout;
+use request::{Action, CharacterSubscription, EventNames, EventSubscription, WorldSubscription};
+use zone::ZoneId;
+use {AchievementId, CharacterId, ExperienceId, FacilityId, Id, ItemId, LoadoutId, MetagameEventId,
+     OutfitId, Service, SkillId, Timestamp, VehicleId, WorldId};
 
-#[derive(Deserialize, PartialEq, Debug)]
+pub mod borrowed;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(tag = "event_name")]
 pub enum Event {
-    PlayerLogin {
-        #[serde(deserialize_with = "deserialize_fromstr")] character_id: CharacterId,
-        #[serde(deserialize_with = "deserialize_fromstr")] timestamp: Timestamp,
-        #[serde(deserialize_with = "deserialize_fromstr")] world_id: WorldId,
-    },
-    PlayerLogout {
-        #[serde(deserialize_with = "deserialize_fromstr")] character_id: CharacterId,
-        #[serde(deserialize_with = "deserialize_fromstr")] timestamp: Timestamp,
-        #[serde(deserialize_with = "deserialize_fromstr")] world_id: WorldId,
-    },
+    AchievementEarned(AchievementEarned),
+    BattleRankUp(BattleRankUp),
+    Death(Death),
+    ItemAdded(ItemAdded),
+    SkillAdded(SkillAdded),
+    VehicleDestroy(VehicleDestroy),
+    GainExperience(GainExperience),
+    PlayerFacilityCapture(PlayerFacilityCapture),
+    PlayerFacilityDefend(PlayerFacilityDefend),
+    ContinentLock(ContinentLock),
+    ContinentUnlock(ContinentUnlock),
+    FacilityControl(FacilityControl),
+    MetagameEvent(MetagameEvent),
+    PlayerLogin(PlayerLogin),
+    PlayerLogout(PlayerLogout),
+}
+
+/// Evaluates `$body` with `$payload` bound to the payload of any `Event` variant.
+macro_rules! match_event {
+    ($event:expr, $payload:ident => $body:expr) => {
+        match *$event {
+            Event::AchievementEarned(ref $payload) => $body,
+            Event::BattleRankUp(ref $payload) => $body,
+            Event::Death(ref $payload) => $body,
+            Event::ItemAdded(ref $payload) => $body,
+            Event::SkillAdded(ref $payload) => $body,
+            Event::VehicleDestroy(ref $payload) => $body,
+            Event::GainExperience(ref $payload) => $body,
+            Event::PlayerFacilityCapture(ref $payload) => $body,
+            Event::PlayerFacilityDefend(ref $payload) => $body,
+            Event::ContinentLock(ref $payload) => $body,
+            Event::ContinentUnlock(ref $payload) => $body,
+            Event::FacilityControl(ref $payload) => $body,
+            Event::MetagameEvent(ref $payload) => $body,
+            Event::PlayerLogin(ref $payload) => $body,
+            Event::PlayerLogout(ref $payload) => $body,
+        }
+    };
+}
+
+impl Event {
+    /// The name to subscribe to this event with, never `EventNames::GainExperienceId`.
+    pub fn event_name(&self) -> EventNames {
+        match *self {
+            Event::AchievementEarned(_) => EventNames::AchievementEarned,
+            Event::BattleRankUp(_) => EventNames::BattleRankUp,
+            Event::Death(_) => EventNames::Death,
+            Event::ItemAdded(_) => EventNames::ItemAdded,
+            Event::SkillAdded(_) => EventNames::SkillAdded,
+            Event::VehicleDestroy(_) => EventNames::VehicleDestroy,
+            Event::GainExperience(_) => EventNames::GainExperience,
+            Event::PlayerFacilityCapture(_) => EventNames::PlayerFacilityCapture,
+            Event::PlayerFacilityDefend(_) => EventNames::PlayerFacilityDefend,
+            Event::ContinentLock(_) => EventNames::ContinentLock,
+            Event::ContinentUnlock(_) => EventNames::ContinentUnlock,
+            Event::FacilityControl(_) => EventNames::FacilityControl,
+            Event::MetagameEvent(_) => EventNames::MetagameEvent,
+            Event::PlayerLogin(_) => EventNames::PlayerLogin,
+            Event::PlayerLogout(_) => EventNames::PlayerLogout,
+        }
+    }
+
+    pub fn timestamp(&self) -> Timestamp {
+        match_event!(self, event => event.timestamp)
+    }
+
+    pub fn world_id(&self) -> WorldId {
+        match_event!(self, event => event.world_id)
+    }
+
+    /// The zone, which is unknown only for `PlayerLogin` and `PlayerLogout`.
+    pub fn zone_id(&self) -> Option<ZoneId> {
+        match *self {
+            Event::AchievementEarned(ref event) => Some(event.zone_id),
+            Event::BattleRankUp(ref event) => Some(event.zone_id),
+            Event::Death(ref event) => Some(event.zone_id),
+            Event::ItemAdded(ref event) => Some(event.zone_id),
+            Event::SkillAdded(ref event) => Some(event.zone_id),
+            Event::VehicleDestroy(ref event) => Some(event.zone_id),
+            Event::GainExperience(ref event) => Some(event.zone_id),
+            Event::PlayerFacilityCapture(ref event) => Some(event.zone_id),
+            Event::PlayerFacilityDefend(ref event) => Some(event.zone_id),
+            Event::ContinentLock(ref event) => Some(event.zone_id),
+            Event::ContinentUnlock(ref event) => Some(event.zone_id),
+            Event::FacilityControl(ref event) => Some(event.zone_id),
+            Event::MetagameEvent(ref event) => Some(event.zone_id),
+            Event::PlayerLogin(_) | Event::PlayerLogout(_) => None,
+        }
+    }
+
+    /// The character the event is about, e.g. the victim of a `Death`.
+    ///
+    /// This is `None` for world events.
+    pub fn character_id(&self) -> Option<CharacterId> {
+        match *self {
+            Event::AchievementEarned(ref event) => Some(event.character_id),
+            Event::BattleRankUp(ref event) => Some(event.character_id),
+            Event::Death(ref event) => Some(event.character_id),
+            Event::ItemAdded(ref event) => Some(event.character_id),
+            Event::SkillAdded(ref event) => Some(event.character_id),
+            Event::VehicleDestroy(ref event) => Some(event.character_id),
+            Event::GainExperience(ref event) => Some(event.character_id),
+            Event::PlayerFacilityCapture(ref event) => Some(event.character_id),
+            Event::PlayerFacilityDefend(ref event) => Some(event.character_id),
+            Event::PlayerLogin(ref event) => Some(event.character_id),
+            Event::PlayerLogout(ref event) => Some(event.character_id),
+            Event::ContinentLock(_)
+            | Event::ContinentUnlock(_)
+            | Event::FacilityControl(_)
+            | Event::MetagameEvent(_) => None,
+        }
+    }
+
+    /// The other character involved, i.e. the attacker of a `Death` or `VehicleDestroy`, or
+    /// the `other_id` of a `GainExperience`.
+    ///
+    /// The `other_id` is not necessarily a character, depending on the experience. Zero ids,
+    /// e.g. of environmental deaths, are `None`.
+    pub fn other_character_id(&self) -> Option<CharacterId> {
+        let other_id = match *self {
+            Event::Death(ref event) => event.attacker_character_id,
+            Event::VehicleDestroy(ref event) => event.attacker_character_id,
+            Event::GainExperience(ref event) => CharacterId::new(event.other_id),
+            _ => return None,
+        };
+
+        if other_id.get() == 0 {
+            None
+        } else {
+            Some(other_id)
+        }
+    }
+
+    /// All distinct characters involved, i.e. `character_id` and `other_character_id`.
+    pub fn character_ids(&self) -> impl Iterator<Item = CharacterId> {
+        let character_id = self.character_id();
+        let other_character_id = self.other_character_id().filter(|&other_character_id| {
+            Some(other_character_id) != character_id
+        });
+
+        IntoIterator::into_iter([character_id, other_character_id]).flatten()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct AchievementEarned {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub achievement_id: AchievementId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BattleRankUp {
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub battle_rank: u64,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Death {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub attacker_fire_mode_id: Id,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_loadout_id: LoadoutId,
+    /// The faction the attacker fights for, which only newer events carry.
+    #[serde(default, deserialize_with = "deserialize_optional_fromstr",
+            serialize_with = "serialize_optional_tostring", skip_serializing_if = "Option::is_none")]
+    pub attacker_team_id: Option<Id>,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_vehicle_id: VehicleId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_weapon_id: ItemId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_loadout_id: LoadoutId,
+    #[serde(deserialize_with = "deserialize_intbool", serialize_with = "serialize_intbool")]
+    pub is_critical: bool,
+    #[serde(deserialize_with = "deserialize_intbool", serialize_with = "serialize_intbool")]
+    pub is_headshot: bool,
+    /// The faction the victim fights for, which only newer events carry.
+    #[serde(default, deserialize_with = "deserialize_optional_fromstr",
+            serialize_with = "serialize_optional_tostring", skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<Id>,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    /// The vehicle the victim died in, `0` if none.
+    #[serde(deserialize_with = "deserialize_fromstr")] pub vehicle_id: VehicleId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+impl Death {
+    pub fn attacker_loadout(&self) -> Option<Loadout> {
+        Loadout::try_from(self.attacker_loadout_id).ok()
+    }
+
+    pub fn character_loadout(&self) -> Option<Loadout> {
+        Loadout::try_from(self.character_loadout_id).ok()
+    }
+
+    /// The faction the attacker fights for, from `attacker_team_id` or else the loadout.
+    ///
+    /// This is `None` for NSO without `attacker_team_id`, since their loadouts do not tell which
+    /// empire they fight for.
+    pub fn attacker_team(&self) -> Option<Faction> {
+        team(self.attacker_team_id, self.attacker_loadout())
+    }
+
+    /// The faction the victim fights for, from `team_id` or else the loadout.
+    ///
+    /// This is `None` for NSO without `team_id`, like `attacker_team`.
+    pub fn team(&self) -> Option<Faction> {
+        team(self.team_id, self.character_loadout())
+    }
+
+    /// Classifies the death by who caused it.
+    ///
+    /// Deaths between characters of unknown teams, e.g. NSO on older events, count as kills.
+    pub fn kind(&self) -> DeathKind {
+        if self.attacker_character_id.get() == 0 {
+            return DeathKind::Environmental;
+        }
+        if self.attacker_character_id == self.character_id {
+            return DeathKind::Suicide;
+        }
+
+        match (self.attacker_team(), self.team()) {
+            (Some(attacker_team), Some(team)) if attacker_team == team => DeathKind::Teamkill,
+            _ => DeathKind::Kill,
+        }
+    }
+}
+
+fn team(team_id: Option<Id>, loadout: Option<Loadout>) -> Option<Faction> {
+    match team_id.and_then(|team_id| Faction::try_from(team_id).ok()) {
+        Some(team) => Some(team),
+        None => loadout
+            .map(|loadout| loadout.faction)
+            .filter(|&faction| faction != Faction::Nso),
+    }
+}
+
+/// What caused a `Death`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DeathKind {
+    /// Killed by an enemy.
+    Kill,
+    /// Killed by a character of the same team.
+    Teamkill,
+    Suicide,
+    /// Killed without an attacking character, e.g. by falling.
+    Environmental,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ItemAdded {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    pub context: String,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub item_count: u64,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub item_id: ItemId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct SkillAdded {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub skill_id: SkillId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct VehicleDestroy {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_loadout_id: LoadoutId,
+    /// The faction the attacker fights for, which only newer events carry.
+    #[serde(default, deserialize_with = "deserialize_optional_fromstr",
+            serialize_with = "serialize_optional_tostring", skip_serializing_if = "Option::is_none")]
+    pub attacker_team_id: Option<Id>,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_vehicle_id: VehicleId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub attacker_weapon_id: ItemId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub facility_id: FacilityId,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub faction_id: Id,
+    /// The faction the owner of the vehicle fights for, which only newer events carry.
+    #[serde(default, deserialize_with = "deserialize_optional_fromstr",
+            serialize_with = "serialize_optional_tostring", skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<Id>,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub vehicle_id: VehicleId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+impl VehicleDestroy {
+    pub fn attacker_loadout(&self) -> Option<Loadout> {
+        Loadout::try_from(self.attacker_loadout_id).ok()
+    }
+
+    /// The faction of the destroyed vehicle.
+    pub fn faction(&self) -> Option<Faction> {
+        Faction::try_from(self.faction_id).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct GainExperience {
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub amount: u64,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub experience_id: ExperienceId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub loadout_id: LoadoutId,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub other_id: Id,
+    /// The faction the character fights for, which only newer events carry.
+    #[serde(default, deserialize_with = "deserialize_optional_fromstr",
+            serialize_with = "serialize_optional_tostring", skip_serializing_if = "Option::is_none")]
+    pub team_id: Option<Id>,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+impl GainExperience {
+    pub fn experience(&self) -> Option<Experience> {
+        Experience::try_from(self.experience_id).ok()
+    }
+
+    pub fn loadout(&self) -> Option<Loadout> {
+        Loadout::try_from(self.loadout_id).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PlayerFacilityCapture {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub facility_id: FacilityId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub outfit_id: OutfitId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PlayerFacilityDefend {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub facility_id: FacilityId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub outfit_id: OutfitId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ContinentLock {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub metagame_event_id: MetagameEventId,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub nc_population: u64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub previous_faction: Id,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub tr_population: u64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub triggering_faction: Id,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub vs_population: u64,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+impl ContinentLock {
+    pub fn triggering_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.triggering_faction).ok()
+    }
+
+    pub fn previous_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.previous_faction).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ContinentUnlock {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub metagame_event_id: MetagameEventId,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub nc_population: u64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub previous_faction: Id,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub tr_population: u64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub triggering_faction: Id,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub vs_population: u64,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+impl ContinentUnlock {
+    pub fn triggering_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.triggering_faction).ok()
+    }
+
+    pub fn previous_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.previous_faction).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct FacilityControl {
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub duration_held: u64,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub facility_id: FacilityId,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub new_faction_id: Id,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub old_faction_id: Id,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub outfit_id: OutfitId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+impl FacilityControl {
+    pub fn new_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.new_faction_id).ok()
+    }
+
+    pub fn old_faction(&self) -> Option<Faction> {
+        Faction::try_from(self.old_faction_id).ok()
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct MetagameEvent {
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_float")]
+    pub experience_bonus: f64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_float")]
+    pub faction_nc: f64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_float")]
+    pub faction_tr: f64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_float")]
+    pub faction_vs: f64,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub instance_id: Id,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub metagame_event_id: MetagameEventId,
+    #[serde(deserialize_with = "deserialize_fromstr", serialize_with = "serialize_tostring")]
+    pub metagame_event_state: Id,
+    pub metagame_event_state_name: String,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub zone_id: ZoneId,
+}
+
+impl MetagameEvent {
+    pub fn state(&self) -> Option<MetagameEventState> {
+        MetagameEventState::ALL
+            .iter()
+            .cloned()
+            .find(|state| state.id() == self.metagame_event_state)
+    }
+}
+
+/// The known states of a `MetagameEvent`, i.e. an alert.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[repr(u64)]
+pub enum MetagameEventState {
+    Started = 135,
+    Restarted = 136,
+    Canceled = 137,
+    Ended = 138,
+    ExperienceBonusChanged = 139,
+}
+
+impl MetagameEventState {
+    pub const ALL: [MetagameEventState; 5] = [
+        MetagameEventState::Started,
+        MetagameEventState::Restarted,
+        MetagameEventState::Canceled,
+        MetagameEventState::Ended,
+        MetagameEventState::ExperienceBonusChanged,
+    ];
+
+    pub fn id(self) -> Id {
+        self as Id
+    }
+
+    /// The name as in `metagame_event_state_name`.
+    pub fn name(self) -> &'static str {
+        use self::MetagameEventState::*;
+
+        match self {
+            Started => "started",
+            Restarted => "restarted",
+            Canceled => "canceled",
+            Ended => "ended",
+            ExperienceBonusChanged => "xp bonus changed",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PlayerLogin {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct PlayerLogout {
+    #[serde(deserialize_with = "deserialize_fromstr")] pub character_id: CharacterId,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub timestamp: Timestamp,
+    #[serde(deserialize_with = "deserialize_fromstr")] pub world_id: WorldId,
+}
+
+/// The subscription as confirmed by the server, after applying all actions so far.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Subscription {
     pub character_count: u64,
-    // TODO: request::EventNames minus GainExperienceId
-    pub event_names: Vec<String>,
+    pub event_names: EventSubscription,
     pub logical_and_characters_with_worlds: bool,
-    // TODO: request::WorldSubscription
-    pub worlds: Vec<String>,
+    pub worlds: WorldSubscription,
+}
+
+impl Subscription {
+    /// Whether this confirms that `action` was applied.
+    ///
+    /// For `Subscribe`, everything subscribed to must be included. Only the number of characters
+    /// is known, so subscribed characters are checked against that. For `ClearSubscribe`,
+    /// nothing cleared may be included. Other actions are always confirmed.
+    pub fn confirms(&self, action: &Action) -> bool {
+        match *action {
+            Action::Subscribe {
+                ref event_names,
+                ref characters,
+                logical_and_characters_with_worlds,
+                ref worlds,
+                ..
+            } => {
+                event_names
+                    .as_ref()
+                    .map_or(true, |event_names| self.event_names.includes(event_names))
+                    && worlds
+                        .as_ref()
+                        .map_or(true, |worlds| self.worlds.includes(worlds))
+                    && match *characters {
+                        Some(CharacterSubscription::Ids(ref ids)) => {
+                            self.character_count >= ids.len() as u64
+                        }
+                        Some(CharacterSubscription::All) | None => true,
+                    }
+                    && logical_and_characters_with_worlds.map_or(true, |logical_and| {
+                        self.logical_and_characters_with_worlds == logical_and
+                    })
+            }
+            Action::ClearSubscribe {
+                all: Some(true), ..
+            } => {
+                self.event_names == EventSubscription::Ids(vec![])
+                    && self.worlds == WorldSubscription::Ids(vec![])
+                    && self.character_count == 0
+            }
+            Action::ClearSubscribe {
+                ref event_names,
+                ref worlds,
+                ..
+            } => {
+                event_names
+                    .as_ref()
+                    .map_or(true, |event_names| self.event_names.excludes(event_names))
+                    && worlds
+                        .as_ref()
+                        .map_or(true, |worlds| self.worlds.excludes(worlds))
+            }
+            Action::Echo { .. }
+            | Action::RecentCharacterIds { .. }
+            | Action::RecentCharacterIdsCount { .. } => true,
+        }
+    }
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(untagged)]
 pub enum Message {
     ConnectionStateChanged {
@@ -40,6 +614,10 @@ pub enum Message {
         // TODO: EventServerEndpoint / WorldId / request::WorldIds -> bool
         online: HashMap<String, String>,
     },
+    /// The reply to `Action::RecentCharacterIds`.
+    RecentCharacterIds {
+        recent_character_id_list: Vec<CharacterId>,
+    },
     ServiceMessage {
         payload: Event,
     },
@@ -53,23 +631,280 @@ pub enum Message {
     },
 }
 
+/// Serializes to the wire format, including the `type` and `service` fields.
+impl serde::Serialize for Message {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        fn bool_str(value: bool) -> &'static str {
+            if value {
+                "true"
+            } else {
+                "false"
+            }
+        }
+
+        let (mut state, service, kind) = match *self {
+            Message::ConnectionStateChanged { connected } => {
+                let mut state = serializer.serialize_struct("Message", 3)?;
+                state.serialize_field("connected", bool_str(connected))?;
+                (state, Service::Push, "connectionStateChanged")
+            }
+            Message::Heartbeat { ref online } => {
+                let mut state = serializer.serialize_struct("Message", 3)?;
+                state.serialize_field("online", online)?;
+                (state, Service::Event, "heartbeat")
+            }
+            Message::RecentCharacterIds {
+                ref recent_character_id_list,
+            } => {
+                let mut state = serializer.serialize_struct("Message", 3)?;
+                state.serialize_field("recent_character_id_list", recent_character_id_list)?;
+                (state, Service::Event, "serviceMessage")
+            }
+            Message::ServiceMessage { ref payload } => {
+                let mut state = serializer.serialize_struct("Message", 3)?;
+                state.serialize_field("payload", payload)?;
+                (state, Service::Event, "serviceMessage")
+            }
+            Message::ServiceStateChanged { online, ref detail } => {
+                let mut state = serializer.serialize_struct("Message", 4)?;
+                state.serialize_field("detail", detail)?;
+                state.serialize_field("online", bool_str(online))?;
+                (state, Service::Event, "serviceStateChanged")
+            }
+            Message::Subscription { ref subscription } => {
+                let mut state = serializer.serialize_struct("Message", 1)?;
+                state.serialize_field("subscription", subscription)?;
+                return state.end();
+            }
+        };
+        state.serialize_field("service", &service)?;
+        state.serialize_field("type", kind)?;
+        state.end()
+    }
+}
+
+/// Deserializes a `FromStr` value from a string, a number or a bool.
+///
+/// The push API string-encodes all of these, but normalized recordings may not. Numbers and bools
+/// are parsed without an intermediate `String`, except for floats too long for the stack buffer.
 fn deserialize_fromstr<'de, T, D>(deserializer: D) -> Result<T, D::Error>
 where
     T: std::str::FromStr,
     T::Err: std::fmt::Display,
     D: serde::Deserializer<'de>,
 {
-    use serde::Deserialize;
+    deserializer.deserialize_any(FromStrVisitor(std::marker::PhantomData))
+}
+
+/// Deserializes like `deserialize_fromstr`, for fields that default to `None` when missing.
+fn deserialize_optional_fromstr<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+    D: serde::Deserializer<'de>,
+{
+    deserialize_fromstr(deserializer).map(Some)
+}
+
+struct FromStrVisitor<T>(std::marker::PhantomData<T>);
+
+impl<T> FromStrVisitor<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    /// Parses the `Display` output of a number, using a stack buffer unless it is huge.
+    fn visit_display<V, E>(self, value: V) -> Result<T, E>
+    where
+        V: std::fmt::Display,
+        E: serde::de::Error,
+    {
+        use std::fmt::Write;
+
+        let mut buf = StackBuf {
+            buf: [0; 32],
+            len: 0,
+        };
+        match write!(buf, "{}", value) {
+            Ok(()) => serde::de::Visitor::visit_str(self, buf.as_str()),
+            Err(_) => serde::de::Visitor::visit_str(self, &value.to_string()),
+        }
+    }
+}
+
+impl<'de, T> serde::de::Visitor<'de> for FromStrVisitor<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a string, number or bool")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_str(if value { "true" } else { "false" })
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_display(value)
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_display(value)
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        self.visit_display(value)
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<T, E>
+    where
+        E: serde::de::Error,
+    {
+        value.parse().map_err(E::custom)
+    }
+}
+
+struct StackBuf {
+    buf: [u8; 32],
+    len: usize,
+}
+
+impl StackBuf {
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.buf[..self.len]).expect("only complete strs are written")
+    }
+}
+
+impl std::fmt::Write for StackBuf {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(std::fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+
+        Ok(())
+    }
+}
+
+fn serialize_tostring<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: std::fmt::Display,
+    S: serde::Serializer,
+{
+    serializer.collect_str(value)
+}
+
+/// Serializes `Some` like `serialize_tostring`, for fields skipped when `None`.
+fn serialize_optional_tostring<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: std::fmt::Display,
+    S: serde::Serializer,
+{
+    match *value {
+        Some(ref value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Serializes with the six decimal places the push API uses, e.g. `"25.000000"`.
+fn serialize_float<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.collect_str(&format_args!("{:.6}", value))
+}
+
+fn serialize_intbool<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(if *value { "1" } else { "0" })
+}
+
+/// Deserializes `0` or `1`, either string-encoded or as a number, or a bool as `bool`.
+fn deserialize_intbool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct IntBoolVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for IntBoolVisitor {
+        type Value = bool;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("0 or 1")
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<bool, E>
+        where
+            E: serde::de::Error,
+        {
+            Ok(value)
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<bool, E>
+        where
+            E: serde::de::Error,
+        {
+            match value {
+                0 => Ok(false),
+                1 => Ok(true),
+                value => Err(E::invalid_value(
+                    serde::de::Unexpected::Unsigned(value),
+                    &self,
+                )),
+            }
+        }
 
-    String::deserialize(deserializer)?
-        .parse()
-        .map_err(serde::de::Error::custom)
+        fn visit_str<E>(self, value: &str) -> Result<bool, E>
+        where
+            E: serde::de::Error,
+        {
+            match value {
+                "0" => Ok(false),
+                "1" => Ok(true),
+                value => Err(E::invalid_value(serde::de::Unexpected::Str(value), &self)),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(IntBoolVisitor)
 }
 
 #[cfg(test)]
 // TODO: Replace `assert_eq!(.., json)` with https://docs.serde.rs/serde_test/
 mod tests {
     use super::*;
+    use serde_json;
+    use loadout::Class;
+    use zone::Zone;
+
+    fn assert_serializes_to(message: &Message, input: &str) {
+        let expected: serde_json::Value = serde_json::from_str(input).unwrap();
+        assert_eq!(serde_json::to_value(message).unwrap(), expected);
+    }
 
     #[test]
     fn service_state_changed() {
@@ -87,6 +922,7 @@ mod tests {
         };
 
         assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
     }
 
     #[test]
@@ -101,6 +937,7 @@ mod tests {
         let expected = Message::ConnectionStateChanged { connected: true };
 
         assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
     }
 
     #[test]
@@ -121,14 +958,463 @@ mod tests {
         let deserialized: Message = serde_json::from_str(input).unwrap();
 
         let expected = Message::ServiceMessage {
-            payload: Event::PlayerLogin {
-                character_id: 5428602376718262177,
-                timestamp: 1513785744,
-                world_id: 1,
+            payload: Event::PlayerLogin(PlayerLogin {
+                character_id: CharacterId::new(5428602376718262177),
+                timestamp: Timestamp::from_secs(1513785744),
+                world_id: WorldId::new(1),
+            }),
+        };
+
+        assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+    }
+
+    #[test]
+    fn death() {
+        let input = r#"{
+            "payload": {
+                "attacker_character_id": "5428010618015189713",
+                "attacker_fire_mode_id": "26003",
+                "attacker_loadout_id": "15",
+                "attacker_vehicle_id": "0",
+                "attacker_weapon_id": "26002",
+                "character_id": "5428713425545165425",
+                "character_loadout_id": "4",
+                "event_name": "Death",
+                "is_critical": "0",
+                "is_headshot": "1",
+                "timestamp": "1513785744",
+                "vehicle_id": "0",
+                "world_id": "13",
+                "zone_id": "196969"
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        let expected = Message::ServiceMessage {
+            payload: Event::Death(Death {
+                attacker_character_id: CharacterId::new(5428010618015189713),
+                attacker_fire_mode_id: 26003,
+                attacker_loadout_id: LoadoutId::new(15),
+                attacker_team_id: None,
+                attacker_vehicle_id: VehicleId::new(0),
+                attacker_weapon_id: ItemId::new(26002),
+                character_id: CharacterId::new(5428713425545165425),
+                character_loadout_id: LoadoutId::new(4),
+                is_critical: false,
+                is_headshot: true,
+                team_id: None,
+                timestamp: Timestamp::from_secs(1513785744),
+                vehicle_id: VehicleId::new(0),
+                world_id: WorldId::new(13),
+                zone_id: ZoneId::from_parts(361, 3),
+            }),
+        };
+
+        assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+    }
+
+    #[test]
+    fn death_native_encoding() {
+        let input = r#"{
+            "payload": {
+                "attacker_character_id": 5428010618015189713,
+                "attacker_fire_mode_id": 26003,
+                "attacker_loadout_id": 15,
+                "attacker_vehicle_id": 0,
+                "attacker_weapon_id": 26002,
+                "character_id": 5428713425545165425,
+                "character_loadout_id": 4,
+                "event_name": "Death",
+                "is_critical": false,
+                "is_headshot": true,
+                "timestamp": 1513785744,
+                "vehicle_id": 0,
+                "world_id": 13,
+                "zone_id": 196969
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        match deserialized {
+            Message::ServiceMessage {
+                payload: Event::Death(death),
+            } => {
+                assert_eq!(death.attacker_character_id, CharacterId::new(5428010618015189713));
+                assert!(death.is_headshot);
+                assert_eq!(death.zone_id, ZoneId::from_parts(361, 3));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn deserialize_fromstr_encodings() {
+        #[derive(Deserialize)]
+        struct Value<T>
+        where
+            T: std::str::FromStr,
+            T::Err: std::fmt::Display,
+        {
+            #[serde(deserialize_with = "deserialize_fromstr")] value: T,
+        }
+
+        fn value<T>(input: &str) -> Result<T, serde_json::Error>
+        where
+            T: std::str::FromStr,
+            T::Err: std::fmt::Display,
+        {
+            serde_json::from_str::<Value<T>>(input).map(|value| value.value)
+        }
+
+        assert_eq!(value::<u64>(r#"{ "value": "42" }"#).unwrap(), 42);
+        assert_eq!(value::<u64>(r#"{ "value": 42 }"#).unwrap(), 42);
+        assert_eq!(value::<i64>(r#"{ "value": -42 }"#).unwrap(), -42);
+        assert_eq!(value::<f64>(r#"{ "value": "33.725491" }"#).unwrap(), 33.725491);
+        assert_eq!(value::<f64>(r#"{ "value": 33.725491 }"#).unwrap(), 33.725491);
+        assert_eq!(value::<f64>(r#"{ "value": 1e300 }"#).unwrap(), 1e300);
+        assert!(value::<bool>(r#"{ "value": "true" }"#).unwrap());
+        assert!(value::<bool>(r#"{ "value": true }"#).unwrap());
+        assert!(value::<u64>(r#"{ "value": -1 }"#).is_err());
+        assert!(value::<u64>(r#"{ "value": null }"#).is_err());
+    }
+
+    #[test]
+    fn death_loadouts() {
+        let death = Death {
+            attacker_character_id: CharacterId::new(5428010618015189713),
+            attacker_fire_mode_id: 26003,
+            attacker_loadout_id: LoadoutId::new(15),
+            attacker_team_id: None,
+            attacker_vehicle_id: VehicleId::new(0),
+            attacker_weapon_id: ItemId::new(26002),
+            character_id: CharacterId::new(5428713425545165425),
+            character_loadout_id: LoadoutId::new(4),
+            is_critical: false,
+            is_headshot: true,
+            team_id: None,
+            timestamp: Timestamp::from_secs(1513785744),
+            vehicle_id: VehicleId::new(0),
+            world_id: WorldId::new(13),
+            zone_id: ZoneId::from(Zone::Indar),
+        };
+
+        assert_eq!(
+            death.attacker_loadout(),
+            Some(Loadout::new(Faction::Vs, Class::Infiltrator))
+        );
+        assert_eq!(
+            death.character_loadout(),
+            Some(Loadout::new(Faction::Nc, Class::Medic))
+        );
+    }
+
+    #[test]
+    fn death_kind() {
+        let death = |attacker: (u64, u64), victim: (u64, u64)| Death {
+            attacker_character_id: CharacterId::new(attacker.0),
+            attacker_fire_mode_id: 0,
+            attacker_loadout_id: LoadoutId::new(attacker.1),
+            attacker_team_id: None,
+            attacker_vehicle_id: VehicleId::new(0),
+            attacker_weapon_id: ItemId::new(0),
+            character_id: CharacterId::new(victim.0),
+            character_loadout_id: LoadoutId::new(victim.1),
+            is_critical: false,
+            is_headshot: false,
+            team_id: None,
+            timestamp: Timestamp::from_secs(1513785744),
+            vehicle_id: VehicleId::new(0),
+            world_id: WorldId::new(13),
+            zone_id: ZoneId::from(Zone::Indar),
+        };
+
+        assert_eq!(death((1, 15), (2, 8)).kind(), DeathKind::Kill);
+        assert_eq!(death((1, 15), (2, 17)).kind(), DeathKind::Teamkill);
+        assert_eq!(death((2, 8), (2, 8)).kind(), DeathKind::Suicide);
+        assert_eq!(death((0, 0), (2, 8)).kind(), DeathKind::Environmental);
+
+        let mut nso = death((1, 28), (2, 8));
+        assert_eq!(nso.attacker_team(), None);
+        assert_eq!(nso.kind(), DeathKind::Kill);
+        nso.attacker_team_id = Some(3);
+        nso.team_id = Some(3);
+        assert_eq!(nso.attacker_team(), Some(Faction::Tr));
+        assert_eq!(nso.kind(), DeathKind::Teamkill);
+    }
+
+    #[test]
+    fn death_team_ids() {
+        let input = json!({
+            "attacker_character_id": "5428010618015189713",
+            "attacker_fire_mode_id": "26003",
+            "attacker_loadout_id": "28",
+            "attacker_team_id": "2",
+            "attacker_vehicle_id": "0",
+            "attacker_weapon_id": "26002",
+            "character_id": "5428713425545165425",
+            "character_loadout_id": "4",
+            "event_name": "Death",
+            "is_critical": "0",
+            "is_headshot": "0",
+            "team_id": "2",
+            "timestamp": "1513785744",
+            "vehicle_id": "0",
+            "world_id": "13",
+            "zone_id": "2"
+        });
+        let event: Event = serde_json::from_value(input.clone()).unwrap();
+
+        match event {
+            Event::Death(ref death) => {
+                assert_eq!(death.attacker_team_id, Some(2));
+                assert_eq!(death.kind(), DeathKind::Teamkill);
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(serde_json::to_value(&event).unwrap(), input);
+    }
+
+    #[test]
+    fn event_accessors() {
+        let death = Event::Death(Death {
+            attacker_character_id: CharacterId::new(5428010618015189713),
+            attacker_fire_mode_id: 26003,
+            attacker_loadout_id: LoadoutId::new(15),
+            attacker_team_id: None,
+            attacker_vehicle_id: VehicleId::new(0),
+            attacker_weapon_id: ItemId::new(26002),
+            character_id: CharacterId::new(5428713425545165425),
+            character_loadout_id: LoadoutId::new(4),
+            is_critical: false,
+            is_headshot: true,
+            team_id: None,
+            timestamp: Timestamp::from_secs(1513785744),
+            vehicle_id: VehicleId::new(0),
+            world_id: WorldId::new(13),
+            zone_id: ZoneId::from(Zone::Indar),
+        });
+        assert_eq!(death.event_name(), EventNames::Death);
+        assert_eq!(death.timestamp(), Timestamp::from_secs(1513785744));
+        assert_eq!(death.world_id(), WorldId::new(13));
+        assert_eq!(death.zone_id(), Some(ZoneId::from(Zone::Indar)));
+        assert_eq!(
+            death.character_ids().collect::<Vec<_>>(),
+            vec![
+                CharacterId::new(5428713425545165425),
+                CharacterId::new(5428010618015189713),
+            ]
+        );
+
+        let login = Event::PlayerLogin(PlayerLogin {
+            character_id: CharacterId::new(5428602376718262177),
+            timestamp: Timestamp::from_secs(1513785744),
+            world_id: WorldId::new(1),
+        });
+        assert_eq!(login.event_name(), EventNames::PlayerLogin);
+        assert_eq!(login.zone_id(), None);
+        assert_eq!(
+            login.character_ids().collect::<Vec<_>>(),
+            vec![CharacterId::new(5428602376718262177)]
+        );
+
+        let facility_control = Event::FacilityControl(FacilityControl {
+            duration_held: 3600,
+            facility_id: FacilityId::new(222280),
+            new_faction_id: 2,
+            old_faction_id: 3,
+            outfit_id: OutfitId::new(0),
+            timestamp: Timestamp::from_secs(1513785744),
+            world_id: WorldId::new(1),
+            zone_id: ZoneId::from(Zone::Oshur),
+        });
+        assert!(facility_control.event_name().is_world_event());
+        assert_eq!(facility_control.character_ids().count(), 0);
+    }
+
+    #[test]
+    fn gain_experience() {
+        let input = r#"{
+            "payload": {
+                "amount": "100",
+                "character_id": "5428010618015189713",
+                "event_name": "GainExperience",
+                "experience_id": "7",
+                "loadout_id": "4",
+                "other_id": "5428713425545165425",
+                "team_id": "1",
+                "timestamp": "1513785744",
+                "world_id": "17",
+                "zone_id": "2"
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        let expected = Message::ServiceMessage {
+            payload: Event::GainExperience(GainExperience {
+                amount: 100,
+                character_id: CharacterId::new(5428010618015189713),
+                experience_id: ExperienceId::new(7),
+                loadout_id: LoadoutId::new(4),
+                other_id: 5428713425545165425,
+                team_id: Some(1),
+                timestamp: Timestamp::from_secs(1513785744),
+                world_id: WorldId::new(17),
+                zone_id: ZoneId::from(Zone::Indar),
+            }),
+        };
+
+        assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+
+        match deserialized {
+            Message::ServiceMessage {
+                payload: Event::GainExperience(gain_experience),
+            } => assert_eq!(gain_experience.experience(), Some(Experience::Revive)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn vehicle_destroy() {
+        let input = r#"{
+            "payload": {
+                "attacker_character_id": "5428010618015189713",
+                "attacker_loadout_id": "15",
+                "attacker_team_id": "1",
+                "attacker_vehicle_id": "0",
+                "attacker_weapon_id": "7214",
+                "character_id": "5428713425545165425",
+                "event_name": "VehicleDestroy",
+                "facility_id": "0",
+                "faction_id": "2",
+                "team_id": "2",
+                "timestamp": "1513785744",
+                "vehicle_id": "4",
+                "world_id": "17",
+                "zone_id": "4"
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        let expected = Message::ServiceMessage {
+            payload: Event::VehicleDestroy(VehicleDestroy {
+                attacker_character_id: CharacterId::new(5428010618015189713),
+                attacker_loadout_id: LoadoutId::new(15),
+                attacker_team_id: Some(1),
+                attacker_vehicle_id: VehicleId::new(0),
+                attacker_weapon_id: ItemId::new(7214),
+                character_id: CharacterId::new(5428713425545165425),
+                facility_id: FacilityId::new(0),
+                faction_id: 2,
+                team_id: Some(2),
+                timestamp: Timestamp::from_secs(1513785744),
+                vehicle_id: VehicleId::new(4),
+                world_id: WorldId::new(17),
+                zone_id: ZoneId::from(Zone::Hossin),
+            }),
+        };
+
+        assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+    }
+
+    #[test]
+    fn facility_control() {
+        let input = r#"{
+            "payload": {
+                "duration_held": "3600",
+                "event_name": "FacilityControl",
+                "facility_id": "222280",
+                "new_faction_id": "2",
+                "old_faction_id": "3",
+                "outfit_id": "37509488620604883",
+                "timestamp": "1513785744",
+                "world_id": "1",
+                "zone_id": "344"
             },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        let expected = Message::ServiceMessage {
+            payload: Event::FacilityControl(FacilityControl {
+                duration_held: 3600,
+                facility_id: FacilityId::new(222280),
+                new_faction_id: 2,
+                old_faction_id: 3,
+                outfit_id: OutfitId::new(37509488620604883),
+                timestamp: Timestamp::from_secs(1513785744),
+                world_id: WorldId::new(1),
+                zone_id: ZoneId::from(Zone::Oshur),
+            }),
         };
 
         assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+    }
+
+    #[test]
+    fn metagame_event() {
+        let input = r#"{
+            "payload": {
+                "event_name": "MetagameEvent",
+                "experience_bonus": "25.000000",
+                "faction_nc": "33.725491",
+                "faction_tr": "25.490196",
+                "faction_vs": "40.784313",
+                "instance_id": "24789",
+                "metagame_event_id": "147",
+                "metagame_event_state": "138",
+                "metagame_event_state_name": "ended",
+                "timestamp": "1513785744",
+                "world_id": "10",
+                "zone_id": "8"
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        let expected = Message::ServiceMessage {
+            payload: Event::MetagameEvent(MetagameEvent {
+                experience_bonus: 25.0,
+                faction_nc: 33.725491,
+                faction_tr: 25.490196,
+                faction_vs: 40.784313,
+                instance_id: 24789,
+                metagame_event_id: MetagameEventId::new(147),
+                metagame_event_state: 138,
+                metagame_event_state_name: "ended".to_string(),
+                timestamp: Timestamp::from_secs(1513785744),
+                world_id: WorldId::new(10),
+                zone_id: ZoneId::from(Zone::Esamir),
+            }),
+        };
+
+        assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+
+        match deserialized {
+            Message::ServiceMessage {
+                payload: Event::MetagameEvent(event),
+            } => {
+                let state = event.state().unwrap();
+                assert_eq!(state, MetagameEventState::Ended);
+                assert_eq!(state.name(), event.metagame_event_state_name);
+            }
+            _ => unreachable!(),
+        }
     }
 
     #[test]
@@ -172,9 +1458,33 @@ mod tests {
             "EventServerEndpoint_Miller_10".to_string(),
             "true".to_string(),
         );
-        let expected = Message::Heartbeat { online: online };
+        let expected = Message::Heartbeat { online };
 
         assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+    }
+
+    #[test]
+    fn recent_character_ids() {
+        let input = r#"{
+            "recent_character_id_list": [
+                "5428010618015189713",
+                "5428713425545165425"
+            ],
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        let expected = Message::RecentCharacterIds {
+            recent_character_id_list: vec![
+                CharacterId::new(5428010618015189713),
+                CharacterId::new(5428713425545165425),
+            ],
+        };
+
+        assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
     }
 
     #[test]
@@ -196,12 +1506,105 @@ mod tests {
         let expected = Message::Subscription {
             subscription: Subscription {
                 character_count: 0,
-                event_names: vec!["PlayerLogin".to_string()],
+                event_names: EventSubscription::Ids(vec![EventNames::PlayerLogin]),
                 logical_and_characters_with_worlds: false,
-                worlds: vec!["1".to_string()],
+                worlds: WorldSubscription::Ids(vec![WorldId::new(1)]),
+            },
+        };
+
+        assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+    }
+
+    #[test]
+    fn subscription_all() {
+        let input = r#"{
+            "subscription": {
+                "characterCount": 0,
+                "eventNames": [
+                    "all"
+                ],
+                "logicalAndCharactersWithWorlds": true,
+                "worlds": [
+                    "all"
+                ]
+            }
+        }"#;
+        let deserialized: Message = serde_json::from_str(input).unwrap();
+
+        let expected = Message::Subscription {
+            subscription: Subscription {
+                character_count: 0,
+                event_names: EventSubscription::All,
+                logical_and_characters_with_worlds: true,
+                worlds: WorldSubscription::All,
             },
         };
 
         assert_eq!(deserialized, expected);
+        assert_serializes_to(&deserialized, input);
+    }
+
+    #[test]
+    fn subscription_confirms() {
+        let subscription = Subscription {
+            character_count: 2,
+            event_names: EventSubscription::Ids(vec![EventNames::Death, EventNames::PlayerLogin]),
+            logical_and_characters_with_worlds: false,
+            worlds: WorldSubscription::Ids(vec![WorldId::new(1), WorldId::new(13)]),
+        };
+
+        let subscribe = |event_names, worlds| Action::Subscribe {
+            event_names: Some(EventSubscription::Ids(event_names)),
+            characters: Some(CharacterSubscription::Ids(vec![
+                CharacterId::new(1),
+                CharacterId::new(2),
+            ])),
+            logical_and_characters_with_worlds: None,
+            worlds: Some(WorldSubscription::Ids(worlds)),
+            service: Service::Event,
+        };
+        assert!(subscription.confirms(&subscribe(
+            vec![EventNames::PlayerLogin],
+            vec![WorldId::new(13)]
+        )));
+        assert!(!subscription.confirms(&subscribe(
+            vec![EventNames::PlayerLogout],
+            vec![WorldId::new(13)]
+        )));
+        assert!(!subscription.confirms(&subscribe(
+            vec![EventNames::PlayerLogin],
+            vec![WorldId::new(17)]
+        )));
+
+        let clear_subscribe = Action::ClearSubscribe {
+            all: None,
+            event_names: Some(EventSubscription::Ids(vec![EventNames::PlayerLogout])),
+            characters: None,
+            worlds: None,
+            service: Service::Event,
+        };
+        assert!(subscription.confirms(&clear_subscribe));
+
+        let clear_subscribe_all = Action::ClearSubscribe {
+            all: Some(true),
+            event_names: None,
+            characters: None,
+            worlds: None,
+            service: Service::Event,
+        };
+        assert!(!subscription.confirms(&clear_subscribe_all));
+
+        let all = Subscription {
+            character_count: 2,
+            event_names: EventSubscription::All,
+            logical_and_characters_with_worlds: false,
+            worlds: WorldSubscription::All,
+        };
+        assert!(all.confirms(&subscribe(
+            vec![EventNames::PlayerLogout],
+            vec![WorldId::new(17)]
+        )));
+        assert!(!all.confirms(&clear_subscribe));
     }
 }
@@ -0,0 +1,393 @@
+//! Zero-copy variants of `Message` and `Event` that borrow from the frame they are parsed from.
+//!
+//! Unlike their owned counterparts, these are tied to `serde_json` and parse event payloads
+//! without buffering them first, so that the common numeric-only events allocate nothing.
+//! Borrowed strings must not contain escape sequences, which the push API never sends.
+
+use std::collections::HashMap;
+
+use serde;
+use serde_json;
+use serde_json::value::RawValue;
+
+use super::{deserialize_fromstr, Subscription};
+use CharacterId;
+
+/// A borrowed `super::Event`, which can also represent events this crate does not know yet.
+#[derive(Debug, Clone)]
+pub enum Event<'a> {
+    Known(super::Event),
+    Unknown {
+        event_name: &'a str,
+        payload: &'a RawValue,
+    },
+}
+
+impl<'a> Event<'a> {
+    pub fn from_raw(payload: &'a RawValue) -> Result<Event<'a>, serde_json::Error> {
+        use super::Event::*;
+
+        #[derive(Deserialize)]
+        struct Tag<'a> {
+            event_name: &'a str,
+        }
+
+        let json = payload.get();
+        let event_name = serde_json::from_str::<Tag>(json)?.event_name;
+        let event = match event_name {
+            "AchievementEarned" => AchievementEarned(serde_json::from_str(json)?),
+            "BattleRankUp" => BattleRankUp(serde_json::from_str(json)?),
+            "Death" => Death(serde_json::from_str(json)?),
+            "ItemAdded" => ItemAdded(serde_json::from_str(json)?),
+            "SkillAdded" => SkillAdded(serde_json::from_str(json)?),
+            "VehicleDestroy" => VehicleDestroy(serde_json::from_str(json)?),
+            "GainExperience" => GainExperience(serde_json::from_str(json)?),
+            "PlayerFacilityCapture" => PlayerFacilityCapture(serde_json::from_str(json)?),
+            "PlayerFacilityDefend" => PlayerFacilityDefend(serde_json::from_str(json)?),
+            "ContinentLock" => ContinentLock(serde_json::from_str(json)?),
+            "ContinentUnlock" => ContinentUnlock(serde_json::from_str(json)?),
+            "FacilityControl" => FacilityControl(serde_json::from_str(json)?),
+            "MetagameEvent" => MetagameEvent(serde_json::from_str(json)?),
+            "PlayerLogin" => PlayerLogin(serde_json::from_str(json)?),
+            "PlayerLogout" => PlayerLogout(serde_json::from_str(json)?),
+            event_name => {
+                return Ok(Event::Unknown {
+                    event_name,
+                    payload,
+                })
+            }
+        };
+
+        Ok(Event::Known(event))
+    }
+
+    pub fn as_known(&self) -> Option<&super::Event> {
+        match *self {
+            Event::Known(ref event) => Some(event),
+            Event::Unknown { .. } => None,
+        }
+    }
+
+    pub fn into_known(self) -> Option<super::Event> {
+        match self {
+            Event::Known(event) => Some(event),
+            Event::Unknown { .. } => None,
+        }
+    }
+}
+
+impl<'de: 'a, 'a> serde::Deserialize<'de> for Event<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Event<'a>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let payload = <&'a RawValue>::deserialize(deserializer)?;
+
+        Event::from_raw(payload).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A borrowed `super::Message`.
+#[derive(Debug, Clone)]
+pub enum Message<'a> {
+    ConnectionStateChanged { connected: bool },
+    Heartbeat { online: HashMap<&'a str, &'a str> },
+    RecentCharacterIds { recent_character_id_list: Vec<CharacterId> },
+    ServiceMessage { payload: Event<'a> },
+    ServiceStateChanged { online: bool, detail: &'a str },
+    Subscription { subscription: Subscription },
+}
+
+impl<'de: 'a, 'a> serde::Deserialize<'de> for Message<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Message<'a>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        struct RawMessage<'a> {
+            #[serde(rename = "type", borrow)]
+            kind: Option<&'a str>,
+            #[serde(borrow)]
+            connected: Option<&'a RawValue>,
+            #[serde(borrow)]
+            online: Option<&'a RawValue>,
+            #[serde(borrow)]
+            payload: Option<&'a RawValue>,
+            recent_character_id_list: Option<Vec<CharacterId>>,
+            #[serde(borrow)]
+            detail: Option<&'a str>,
+            subscription: Option<Subscription>,
+        }
+
+        fn fromstr<T, E>(raw: &RawValue) -> Result<T, E>
+        where
+            T: ::std::str::FromStr,
+            T::Err: ::std::fmt::Display,
+            E: Error,
+        {
+            deserialize_fromstr(&mut serde_json::Deserializer::from_str(raw.get()))
+                .map_err(E::custom)
+        }
+
+        let raw = RawMessage::deserialize(deserializer)?;
+        let message = match (raw.kind, raw.subscription) {
+            (Some("connectionStateChanged"), _) => Message::ConnectionStateChanged {
+                connected: fromstr(
+                    raw.connected
+                        .ok_or_else(|| D::Error::missing_field("connected"))?,
+                )?,
+            },
+            (Some("heartbeat"), _) => Message::Heartbeat {
+                online: serde_json::from_str(
+                    raw.online
+                        .ok_or_else(|| D::Error::missing_field("online"))?
+                        .get(),
+                )
+                .map_err(D::Error::custom)?,
+            },
+            (Some("serviceMessage"), _) => match raw.recent_character_id_list {
+                Some(recent_character_id_list) => Message::RecentCharacterIds {
+                    recent_character_id_list,
+                },
+                None => Message::ServiceMessage {
+                    payload: Event::from_raw(
+                        raw.payload
+                            .ok_or_else(|| D::Error::missing_field("payload"))?,
+                    )
+                    .map_err(D::Error::custom)?,
+                },
+            },
+            (Some("serviceStateChanged"), _) => Message::ServiceStateChanged {
+                online: fromstr(
+                    raw.online
+                        .ok_or_else(|| D::Error::missing_field("online"))?,
+                )?,
+                detail: raw
+                    .detail
+                    .ok_or_else(|| D::Error::missing_field("detail"))?,
+            },
+            (Some(kind), _) => {
+                return Err(D::Error::unknown_variant(
+                    kind,
+                    &[
+                        "connectionStateChanged",
+                        "heartbeat",
+                        "serviceMessage",
+                        "serviceStateChanged",
+                    ],
+                ))
+            }
+            (None, Some(subscription)) => Message::Subscription { subscription },
+            (None, None) => return Err(D::Error::missing_field("type")),
+        };
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use response;
+    use zone::ZoneId;
+    use {CharacterId, Timestamp, WorldId};
+
+    #[test]
+    fn service_state_changed() {
+        let input = r#"{
+            "detail": "EventServerEndpoint_Cobalt_13",
+            "online": "true",
+            "service": "event",
+            "type": "serviceStateChanged"
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::ServiceStateChanged { online, detail } => {
+                assert!(online);
+                assert_eq!(detail, "EventServerEndpoint_Cobalt_13");
+                let offset = input.find("EventServerEndpoint_Cobalt_13").unwrap();
+                assert_eq!(detail.as_ptr(), input[offset..].as_ptr());
+            }
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+
+    #[test]
+    fn connection_state_changed() {
+        let input = r#"{
+            "connected": "true",
+            "service": "push",
+            "type": "connectionStateChanged"
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::ConnectionStateChanged { connected } => assert!(connected),
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+
+    #[test]
+    fn heartbeat() {
+        let input = r#"{
+            "online": {
+                "EventServerEndpoint_Cobalt_13": "true",
+                "EventServerEndpoint_Connery_1": "false"
+            },
+            "service": "event",
+            "type": "heartbeat"
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::Heartbeat { online } => {
+                assert_eq!(online.len(), 2);
+                assert_eq!(online["EventServerEndpoint_Cobalt_13"], "true");
+                assert_eq!(online["EventServerEndpoint_Connery_1"], "false");
+            }
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+
+    #[test]
+    fn recent_character_ids() {
+        let input = r#"{
+            "recent_character_id_list": ["5428010618015189713", "5428713425545165425"],
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::RecentCharacterIds {
+                recent_character_id_list,
+            } => assert_eq!(
+                recent_character_id_list,
+                vec![
+                    CharacterId::new(5428010618015189713),
+                    CharacterId::new(5428713425545165425),
+                ]
+            ),
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+
+    #[test]
+    fn player_login() {
+        let input = r#"{
+            "payload": {
+                "character_id": "5428602376718262177",
+                "event_name": "PlayerLogin",
+                "timestamp": "1513785744",
+                "world_id": "1"
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::ServiceMessage { payload } => assert_eq!(
+                payload.into_known(),
+                Some(response::Event::PlayerLogin(response::PlayerLogin {
+                    character_id: CharacterId::new(5428602376718262177),
+                    timestamp: Timestamp::from_secs(1513785744),
+                    world_id: WorldId::new(1),
+                }))
+            ),
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+
+    #[test]
+    fn death() {
+        let input = r#"{
+            "payload": {
+                "attacker_character_id": "5428010618015189713",
+                "attacker_fire_mode_id": "26003",
+                "attacker_loadout_id": "15",
+                "attacker_vehicle_id": "0",
+                "attacker_weapon_id": "26002",
+                "character_id": "5428713425545165425",
+                "character_loadout_id": "4",
+                "event_name": "Death",
+                "is_critical": "0",
+                "is_headshot": "1",
+                "timestamp": "1513785744",
+                "vehicle_id": "0",
+                "world_id": "13",
+                "zone_id": "196969"
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::ServiceMessage {
+                payload: Event::Known(response::Event::Death(death)),
+            } => {
+                assert!(death.is_headshot);
+                assert_eq!(death.zone_id, ZoneId::from_parts(361, 3));
+            }
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+
+    #[test]
+    fn unknown_event() {
+        let input = r#"{
+            "payload": {
+                "event_name": "FishScan",
+                "fish_id": "1",
+                "world_id": "13"
+            },
+            "service": "event",
+            "type": "serviceMessage"
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::ServiceMessage {
+                payload:
+                    Event::Unknown {
+                        event_name,
+                        payload,
+                    },
+            } => {
+                assert_eq!(event_name, "FishScan");
+                assert!(payload.get().contains(r#""fish_id": "1""#));
+            }
+            message => panic!("unexpected {:?}", message),
+        }
+
+        assert!(serde_json::from_str::<response::Message>(input).is_err());
+    }
+
+    #[test]
+    fn subscription() {
+        let input = r#"{
+            "subscription": {
+                "characterCount": 0,
+                "eventNames": [
+                    "PlayerLogin"
+                ],
+                "logicalAndCharactersWithWorlds": false,
+                "worlds": [
+                    "1"
+                ]
+            }
+        }"#;
+
+        match serde_json::from_str(input).unwrap() {
+            Message::Subscription { subscription } => assert_eq!(subscription.character_count, 0),
+            message => panic!("unexpected {:?}", message),
+        }
+    }
+
+    #[test]
+    fn unknown_type() {
+        let input = r#"{
+            "service": "event",
+            "type": "fishing"
+        }"#;
+
+        assert!(serde_json::from_str::<Message>(input).is_err());
+    }
+}
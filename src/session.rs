@@ -0,0 +1,336 @@
+//! Reconstruction of play sessions from `PlayerLogin` and `PlayerLogout` events.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::time::Duration;
+
+use handler::EventHandler;
+use response::Event;
+use {CharacterId, Timestamp, WorldId};
+
+/// A character's stay on a world.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Session {
+    pub character_id: CharacterId,
+    pub world_id: WorldId,
+    /// The login, or the first event of the character if its login was not seen.
+    pub start: Timestamp,
+    /// The logout, or the last event of the character if its logout was not seen.
+    pub end: Timestamp,
+    pub end_reason: EndReason,
+}
+
+impl Session {
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_duration_since(self.start)
+    }
+}
+
+/// Why a `Session` ended.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EndReason {
+    Logout,
+    /// The character logged in again, on another world or after the timeout.
+    Relogin,
+    /// A heartbeat reported the world as down.
+    WorldDown,
+    /// The character was not seen for longer than the timeout.
+    Timeout,
+    /// The session was still open when the tracker was finished.
+    Unfinished,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct OpenSession {
+    world_id: WorldId,
+    start: Timestamp,
+    last_seen: Timestamp,
+}
+
+/// Turns a stream of events into completed `Session`s.
+///
+/// Sessions open with a `PlayerLogin`, or with the first other event of a character, and close
+/// with a `PlayerLogout`. Missing logouts are covered by the timeout, heartbeats reporting a world
+/// as down and logins on another world. Repeated logins on the same world within the timeout are
+/// considered duplicates and do not split the session.
+///
+/// Completed sessions are collected until taken with `drain`.
+#[derive(Clone, Debug)]
+pub struct SessionTracker {
+    timeout: Duration,
+    open: HashMap<CharacterId, OpenSession>,
+    completed: Vec<Session>,
+    latest: Option<Timestamp>,
+}
+
+impl SessionTracker {
+    pub fn new(timeout: Duration) -> SessionTracker {
+        SessionTracker {
+            timeout,
+            open: HashMap::new(),
+            completed: Vec::new(),
+            latest: None,
+        }
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        let timestamp = event.timestamp();
+        let world_id = event.world_id();
+        if self.latest.map_or(true, |latest| latest < timestamp) {
+            self.latest = Some(timestamp);
+        }
+
+        let character_id = match event.character_id() {
+            Some(character_id) => character_id,
+            None => return,
+        };
+
+        match *event {
+            Event::PlayerLogin(_) => {
+                if let Some(open) = self.open.get_mut(&character_id) {
+                    let duplicate = open.world_id == world_id
+                        && timestamp.saturating_duration_since(open.last_seen) <= self.timeout;
+                    if duplicate {
+                        open.last_seen = open.last_seen.max(timestamp);
+                        return;
+                    }
+                }
+
+                self.close(character_id, EndReason::Relogin);
+                self.open.insert(
+                    character_id,
+                    OpenSession {
+                        world_id,
+                        start: timestamp,
+                        last_seen: timestamp,
+                    },
+                );
+            }
+            Event::PlayerLogout(_) => {
+                if let Some(open) = self.open.get_mut(&character_id) {
+                    open.last_seen = open.last_seen.max(timestamp);
+                }
+                self.close(character_id, EndReason::Logout);
+            }
+            _ => match self.open.entry(character_id) {
+                Entry::Occupied(mut entry) => {
+                    let open = entry.get_mut();
+                    if open.world_id == world_id {
+                        open.last_seen = open.last_seen.max(timestamp);
+                        return;
+                    }
+
+                    let open = entry.remove();
+                    self.complete(character_id, open, EndReason::Relogin);
+                    self.open.insert(
+                        character_id,
+                        OpenSession {
+                            world_id,
+                            start: timestamp,
+                            last_seen: timestamp,
+                        },
+                    );
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(OpenSession {
+                        world_id,
+                        start: timestamp,
+                        last_seen: timestamp,
+                    });
+                }
+            },
+        }
+    }
+
+    /// Closes the sessions on worlds a heartbeat reports as down.
+    ///
+    /// Heartbeats only tell whether a world is up, so like other missing logouts, the sessions end
+    /// when their characters were last seen.
+    pub fn handle_heartbeat(&mut self, online: &HashMap<String, String>) {
+        let down: Vec<_> = online
+            .iter()
+            .filter(|&(_, online)| online == "false")
+            .filter_map(|(endpoint, _)| heartbeat_world_id(endpoint))
+            .collect();
+        if !down.is_empty() {
+            self.close_where(EndReason::WorldDown, |open| down.contains(&open.world_id));
+        }
+    }
+
+    /// Closes the sessions of characters not seen for longer than the timeout before `now`.
+    pub fn expire(&mut self, now: Timestamp) {
+        let timeout = self.timeout;
+        self.close_where(EndReason::Timeout, |open| {
+            now.saturating_duration_since(open.last_seen) > timeout
+        });
+    }
+
+    /// Closes all open sessions, e.g. at the end of a replay.
+    pub fn finish(&mut self) {
+        self.close_where(EndReason::Unfinished, |_| true);
+    }
+
+    /// The number of currently open sessions.
+    pub fn open_count(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Takes the sessions completed so far, in order of completion, and sessions completed at once
+    /// by start and character.
+    pub fn drain(&mut self) -> ::std::vec::Drain<'_, Session> {
+        self.completed.drain(..)
+    }
+
+    /// Closes the matching sessions ordered by start and character, so that sessions closed at
+    /// once complete in the same order on every run.
+    fn close_where<F>(&mut self, end_reason: EndReason, matches: F)
+    where
+        F: Fn(&OpenSession) -> bool,
+    {
+        let mut closing: Vec<_> = self.open
+            .iter()
+            .filter(|&(_, open)| matches(open))
+            .map(|(&character_id, open)| (open.start, character_id))
+            .collect();
+        closing.sort();
+        for (_, character_id) in closing {
+            self.close(character_id, end_reason);
+        }
+    }
+
+    fn close(&mut self, character_id: CharacterId, end_reason: EndReason) {
+        if let Some(open) = self.open.remove(&character_id) {
+            self.complete(character_id, open, end_reason);
+        }
+    }
+
+    fn complete(&mut self, character_id: CharacterId, open: OpenSession, end_reason: EndReason) {
+        self.completed.push(Session {
+            character_id,
+            world_id: open.world_id,
+            start: open.start,
+            end: open.last_seen,
+            end_reason,
+        });
+    }
+}
+
+impl EventHandler for SessionTracker {
+    fn on_event(&mut self, event: &Event) {
+        self.handle_event(event)
+    }
+
+    /// Uses the latest event seen as the current time to expire sessions.
+    fn on_heartbeat(&mut self, online: &HashMap<String, String>) {
+        self.handle_heartbeat(online);
+        if let Some(latest) = self.latest {
+            self.expire(latest);
+        }
+    }
+}
+
+/// The world of a heartbeat endpoint such as `EventServerEndpoint_Connery_1`.
+fn heartbeat_world_id(endpoint: &str) -> Option<WorldId> {
+    endpoint
+        .rsplit('_')
+        .next()
+        .and_then(|world_id| world_id.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixtures::{login, logout, skill_added};
+
+    fn session(
+        character_id: u64,
+        world_id: u64,
+        start: u64,
+        end: u64,
+        end_reason: EndReason,
+    ) -> Session {
+        Session {
+            character_id: CharacterId::new(character_id),
+            world_id: WorldId::new(world_id),
+            start: Timestamp::from_secs(start),
+            end: Timestamp::from_secs(end),
+            end_reason,
+        }
+    }
+
+    #[test]
+    fn login_logout() {
+        let mut tracker = SessionTracker::new(Duration::from_secs(3600));
+        tracker.handle_event(&login(1, 13, 100));
+        tracker.handle_event(&login(1, 13, 105));
+        tracker.handle_event(&skill_added(1, 13, 200));
+        tracker.handle_event(&logout(1, 13, 300));
+
+        assert_eq!(
+            tracker.drain().collect::<Vec<_>>(),
+            vec![session(1, 13, 100, 300, EndReason::Logout)]
+        );
+        assert_eq!(tracker.open_count(), 0);
+    }
+
+    #[test]
+    fn missing_logout() {
+        let mut tracker = SessionTracker::new(Duration::from_secs(3600));
+        tracker.handle_event(&login(1, 13, 100));
+        tracker.handle_event(&login(1, 17, 200));
+        tracker.handle_event(&skill_added(2, 13, 300));
+        tracker.handle_event(&login(1, 17, 5000));
+        tracker.expire(Timestamp::from_secs(5000));
+        tracker.finish();
+
+        assert_eq!(
+            tracker.drain().collect::<Vec<_>>(),
+            vec![
+                session(1, 13, 100, 100, EndReason::Relogin),
+                session(1, 17, 200, 200, EndReason::Relogin),
+                session(2, 13, 300, 300, EndReason::Timeout),
+                session(1, 17, 5000, 5000, EndReason::Unfinished),
+            ]
+        );
+    }
+
+    #[test]
+    fn world_down() {
+        let mut tracker = SessionTracker::new(Duration::from_secs(3600));
+        tracker.handle_event(&login(1, 1, 100));
+        tracker.handle_event(&login(2, 17, 100));
+
+        let mut online = HashMap::new();
+        online.insert("EventServerEndpoint_Connery_1".to_string(), "false".to_string());
+        online.insert("EventServerEndpoint_Emerald_17".to_string(), "true".to_string());
+        tracker.handle_event(&skill_added(1, 1, 120));
+        tracker.handle_heartbeat(&online);
+
+        assert_eq!(
+            tracker.drain().collect::<Vec<_>>(),
+            vec![session(1, 1, 100, 120, EndReason::WorldDown)]
+        );
+        assert_eq!(tracker.open_count(), 1);
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let mut tracker = SessionTracker::new(Duration::from_secs(3600));
+        for &(character_id, start) in &[(5, 300), (3, 100), (4, 300), (1, 200), (2, 5000)] {
+            tracker.handle_event(&login(character_id, 13, start));
+        }
+        tracker.expire(Timestamp::from_secs(5000));
+        tracker.finish();
+
+        assert_eq!(
+            tracker.drain().collect::<Vec<_>>(),
+            vec![
+                session(3, 13, 100, 100, EndReason::Timeout),
+                session(1, 13, 200, 200, EndReason::Timeout),
+                session(4, 13, 300, 300, EndReason::Timeout),
+                session(5, 13, 300, 300, EndReason::Timeout),
+                session(2, 13, 5000, 5000, EndReason::Unfinished),
+            ]
+        );
+    }
+}
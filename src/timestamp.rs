@@ -0,0 +1,497 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A point in time with second precision, as carried by events.
+///
+/// `Display` formats as RFC 3339 in UTC, e.g. `2017-12-20T16:02:24Z`. `FromStr` parses both that
+/// and the Unix seconds used on the wire, e.g. `1513785744`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    pub const UNIX_EPOCH: Timestamp = Timestamp(0);
+
+    pub const fn from_secs(secs: u64) -> Timestamp {
+        Timestamp(secs)
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn as_secs(self) -> u64 {
+        self.0
+    }
+
+    pub fn now() -> Timestamp {
+        Timestamp::try_from(SystemTime::now()).unwrap_or(Timestamp::UNIX_EPOCH)
+    }
+
+    /// The time elapsed from `earlier` to this timestamp, or `None` if `earlier` is later.
+    pub fn checked_duration_since(self, earlier: Timestamp) -> Option<Duration> {
+        self.0.checked_sub(earlier.0).map(Duration::from_secs)
+    }
+
+    /// The time elapsed from `earlier` to this timestamp, or zero if `earlier` is later.
+    pub fn saturating_duration_since(self, earlier: Timestamp) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    pub fn checked_add(self, duration: Duration) -> Option<Timestamp> {
+        self.0.checked_add(duration.as_secs()).map(Timestamp)
+    }
+
+    pub fn checked_sub(self, duration: Duration) -> Option<Timestamp> {
+        self.0.checked_sub(duration.as_secs()).map(Timestamp)
+    }
+}
+
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = TryFromTimestampError;
+
+    /// Fails for timestamps beyond the range of the platform's `SystemTime`.
+    fn try_from(timestamp: Timestamp) -> Result<SystemTime, TryFromTimestampError> {
+        UNIX_EPOCH
+            .checked_add(Duration::from_secs(timestamp.0))
+            .ok_or(TryFromTimestampError(()))
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TryFromSystemTimeError;
+
+    /// Truncates to whole seconds. Fails for times before the Unix epoch.
+    fn try_from(time: SystemTime) -> Result<Timestamp, TryFromSystemTimeError> {
+        time.duration_since(UNIX_EPOCH)
+            .map(|duration| Timestamp(duration.as_secs()))
+            .map_err(|_| TryFromSystemTimeError(()))
+    }
+}
+
+/// Truncates `duration` to whole seconds.
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, duration: Duration) -> Timestamp {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to timestamp")
+    }
+}
+
+impl AddAssign<Duration> for Timestamp {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+/// Truncates `duration` to whole seconds.
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, duration: Duration) -> Timestamp {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from timestamp")
+    }
+}
+
+impl SubAssign<Duration> for Timestamp {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.0 / SECS_PER_DAY);
+        let secs = self.0 % SECS_PER_DAY;
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = ParseTimestampError;
+
+    fn from_str(s: &str) -> Result<Timestamp, ParseTimestampError> {
+        if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+            s.parse().map(Timestamp).map_err(|_| ParseTimestampError(()))
+        } else {
+            parse_rfc3339(s).ok_or(ParseTimestampError(()))
+        }
+    }
+}
+
+/// Serializes as string-encoded Unix seconds, like the push API does.
+impl serde::Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.0)
+    }
+}
+
+/// Deserializes from Unix seconds, either string-encoded or as a number, or from RFC 3339.
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Timestamp, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct TimestampVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a timestamp as Unix seconds or RFC 3339")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Timestamp, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(Timestamp(value))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Timestamp, E>
+            where
+                E: serde::de::Error,
+            {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Timestamp> for ::chrono::DateTime<::chrono::Utc> {
+    type Error = TryFromTimestampError;
+
+    /// Fails for timestamps beyond the range of `chrono`.
+    fn try_from(
+        timestamp: Timestamp,
+    ) -> Result<::chrono::DateTime<::chrono::Utc>, TryFromTimestampError> {
+        i64::try_from(timestamp.0)
+            .ok()
+            .and_then(|secs| ::chrono::DateTime::from_timestamp(secs, 0))
+            .ok_or(TryFromTimestampError(()))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<::chrono::DateTime<::chrono::Utc>> for Timestamp {
+    type Error = TryFromSystemTimeError;
+
+    /// Truncates to whole seconds. Fails for times before the Unix epoch.
+    fn try_from(
+        time: ::chrono::DateTime<::chrono::Utc>,
+    ) -> Result<Timestamp, TryFromSystemTimeError> {
+        u64::try_from(time.timestamp())
+            .map(Timestamp)
+            .map_err(|_| TryFromSystemTimeError(()))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<Timestamp> for ::time::OffsetDateTime {
+    type Error = TryFromTimestampError;
+
+    /// Fails for timestamps beyond the range of `time`.
+    fn try_from(timestamp: Timestamp) -> Result<::time::OffsetDateTime, TryFromTimestampError> {
+        i64::try_from(timestamp.0)
+            .ok()
+            .and_then(|secs| ::time::OffsetDateTime::from_unix_timestamp(secs).ok())
+            .ok_or(TryFromTimestampError(()))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<::time::OffsetDateTime> for Timestamp {
+    type Error = TryFromSystemTimeError;
+
+    /// Truncates to whole seconds. Fails for times before the Unix epoch.
+    fn try_from(time: ::time::OffsetDateTime) -> Result<Timestamp, TryFromSystemTimeError> {
+        u64::try_from(time.unix_timestamp())
+            .map(Timestamp)
+            .map_err(|_| TryFromSystemTimeError(()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromSystemTimeError(());
+
+impl fmt::Display for TryFromSystemTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("time is before the Unix epoch")
+    }
+}
+
+impl ::std::error::Error for TryFromSystemTimeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromTimestampError(());
+
+impl fmt::Display for TryFromTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("timestamp is out of range")
+    }
+}
+
+impl ::std::error::Error for TryFromTimestampError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimestampError(());
+
+impl fmt::Display for ParseTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid timestamp, expected Unix seconds or RFC 3339")
+    }
+}
+
+impl ::std::error::Error for ParseTimestampError {}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian `(year, month, day)`.
+///
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// The inverse of `civil_from_days`, or `None` before the Unix epoch.
+///
+/// See http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    let year = if month <= 2 { year.checked_sub(1)? } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses `YYYY-MM-DDTHH:MM:SS[.fraction](Z|±HH:MM)`, truncating fractional seconds.
+fn parse_rfc3339(s: &str) -> Option<Timestamp> {
+    fn number(s: &str, range: ::std::ops::Range<usize>) -> Option<u64> {
+        let digits = s.get(range)?;
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    let bytes = s.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !(bytes[10] == b'T' || bytes[10] == b't' || bytes[10] == b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+
+    let year = number(s, 0..4)?;
+    let month = number(s, 5..7)?;
+    let day = number(s, 8..10)?;
+    let hour = number(s, 11..13)?;
+    let minute = number(s, 14..16)?;
+    let second = number(s, 17..19)?;
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23 || minute > 59 || second > 60
+    {
+        return None;
+    }
+
+    let mut rest = &s[19..];
+    if rest.starts_with('.') {
+        let fraction = rest[1..].bytes().take_while(u8::is_ascii_digit).count();
+        if fraction == 0 {
+            return None;
+        }
+        rest = &rest[1 + fraction..];
+    }
+
+    let secs = days_from_civil(year, month, day)? * SECS_PER_DAY + hour * 3600 + minute * 60
+        + second;
+    let secs = match rest {
+        "Z" | "z" => secs,
+        offset if offset.len() == 6 && offset.as_bytes()[3] == b':' => {
+            let offset_secs = number(offset, 1..3)? * 3600 + number(offset, 4..6)? * 60;
+            match offset.as_bytes()[0] {
+                b'+' => secs.checked_sub(offset_secs)?,
+                b'-' => secs.checked_add(offset_secs)?,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(Timestamp(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn display() {
+        assert_eq!(Timestamp::UNIX_EPOCH.to_string(), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            Timestamp::from_secs(1513785744).to_string(),
+            "2017-12-20T16:02:24Z"
+        );
+        assert_eq!(
+            Timestamp::from_secs(951782400).to_string(),
+            "2000-02-29T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn fromstr() {
+        assert_eq!("1513785744".parse(), Ok(Timestamp::from_secs(1513785744)));
+        assert_eq!(
+            "2017-12-20T16:02:24Z".parse(),
+            Ok(Timestamp::from_secs(1513785744))
+        );
+        assert_eq!(
+            "2017-12-20T17:02:24.123+01:00".parse(),
+            Ok(Timestamp::from_secs(1513785744))
+        );
+        assert_eq!(
+            "2017-12-20 15:02:24-01:00".parse(),
+            Ok(Timestamp::from_secs(1513785744))
+        );
+
+        assert!("".parse::<Timestamp>().is_err());
+        assert!("2017-02-29T00:00:00Z".parse::<Timestamp>().is_err());
+        assert!("1969-12-31T23:59:59Z".parse::<Timestamp>().is_err());
+        assert!("2017-12-20T16:02:24".parse::<Timestamp>().is_err());
+    }
+
+    #[test]
+    fn display_fromstr_roundtrip() {
+        for secs in (0..4_000_000_000).step_by(86_399 * 7) {
+            let timestamp = Timestamp::from_secs(secs);
+            assert_eq!(timestamp.to_string().parse(), Ok(timestamp));
+        }
+    }
+
+    #[test]
+    fn system_time() {
+        let timestamp = Timestamp::from_secs(1513785744);
+        let time = SystemTime::try_from(timestamp).unwrap();
+        assert_eq!(Timestamp::try_from(time), Ok(timestamp));
+        assert_eq!(
+            SystemTime::try_from(Timestamp::from_secs(u64::MAX)),
+            Err(TryFromTimestampError(()))
+        );
+        assert_eq!(
+            Timestamp::try_from(time + Duration::from_millis(999)),
+            Ok(timestamp)
+        );
+
+        assert!(Timestamp::try_from(UNIX_EPOCH - Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn arithmetic() {
+        let timestamp = Timestamp::from_secs(1513785744);
+        let later = timestamp + Duration::from_secs(60);
+        assert!(later > timestamp);
+        assert_eq!(later - Duration::from_secs(60), timestamp);
+        assert_eq!(
+            later.checked_duration_since(timestamp),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(timestamp.checked_duration_since(later), None);
+        assert_eq!(timestamp.saturating_duration_since(later), Duration::from_secs(0));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        let timestamp = Timestamp::from_secs(1513785744);
+        let time = ::chrono::DateTime::<::chrono::Utc>::try_from(timestamp).unwrap();
+        assert_eq!(time.timestamp(), 1513785744);
+        assert_eq!(Timestamp::try_from(time), Ok(timestamp));
+
+        assert_eq!(
+            ::chrono::DateTime::<::chrono::Utc>::try_from(Timestamp::from_secs(u64::MAX)),
+            Err(TryFromTimestampError(()))
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time() {
+        let timestamp = Timestamp::from_secs(1513785744);
+        let time = ::time::OffsetDateTime::try_from(timestamp).unwrap();
+        assert_eq!(time.unix_timestamp(), 1513785744);
+        assert_eq!(Timestamp::try_from(time), Ok(timestamp));
+
+        assert_eq!(
+            ::time::OffsetDateTime::try_from(Timestamp::from_secs(u64::MAX)),
+            Err(TryFromTimestampError(()))
+        );
+    }
+
+    #[test]
+    fn serde() {
+        let timestamp = Timestamp::from_secs(1513785744);
+        assert_eq!(serde_json::to_value(timestamp).unwrap(), json!("1513785744"));
+
+        assert_eq!(
+            serde_json::from_value::<Timestamp>(json!("1513785744")).unwrap(),
+            timestamp
+        );
+        assert_eq!(
+            serde_json::from_value::<Timestamp>(json!(1513785744)).unwrap(),
+            timestamp
+        );
+        assert_eq!(
+            serde_json::from_value::<Timestamp>(json!("2017-12-20T16:02:24Z")).unwrap(),
+            timestamp
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use serde;
+
+use {Environment, WorldId};
+
+/// The known game servers ("worlds") of all environments.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[repr(u64)]
+pub enum World {
+    Connery = 1,
+    Miller = 10,
+    Cobalt = 13,
+    Emerald = 17,
+    Jaeger = 19,
+    Briggs = 25,
+    Soltech = 40,
+    Genudine = 1000,
+    Palos = 1001,
+    Crux = 1002,
+    Searhus = 1003,
+    Xelas = 1004,
+    Ceres = 2000,
+    Lithcorp = 2001,
+    Rashnu = 2002,
+}
+
+impl World {
+    pub const ALL: [World; 15] = [
+        World::Connery,
+        World::Miller,
+        World::Cobalt,
+        World::Emerald,
+        World::Jaeger,
+        World::Briggs,
+        World::Soltech,
+        World::Genudine,
+        World::Palos,
+        World::Crux,
+        World::Searhus,
+        World::Xelas,
+        World::Ceres,
+        World::Lithcorp,
+        World::Rashnu,
+    ];
+
+    pub fn id(self) -> WorldId {
+        WorldId::new(self as u64)
+    }
+
+    pub fn name(self) -> &'static str {
+        use self::World::*;
+
+        match self {
+            Connery => "Connery",
+            Miller => "Miller",
+            Cobalt => "Cobalt",
+            Emerald => "Emerald",
+            Jaeger => "Jaeger",
+            Briggs => "Briggs",
+            Soltech => "Soltech",
+            Genudine => "Genudine",
+            Palos => "Palos",
+            Crux => "Crux",
+            Searhus => "Searhus",
+            Xelas => "Xelas",
+            Ceres => "Ceres",
+            Lithcorp => "Lithcorp",
+            Rashnu => "Rashnu",
+        }
+    }
+
+    /// The environment whose push server streams this world's events.
+    pub fn environment(self) -> Environment {
+        use self::World::*;
+
+        match self {
+            Connery | Miller | Cobalt | Emerald | Jaeger | Briggs | Soltech => Environment::Pc,
+            Genudine | Palos | Crux | Searhus | Xelas => Environment::Ps4Us,
+            Ceres | Lithcorp | Rashnu => Environment::Ps4Eu,
+        }
+    }
+}
+
+impl From<World> for WorldId {
+    fn from(world: World) -> WorldId {
+        world.id()
+    }
+}
+
+impl TryFrom<WorldId> for World {
+    type Error = TryFromWorldIdError;
+
+    fn try_from(id: WorldId) -> Result<World, TryFromWorldIdError> {
+        World::ALL
+            .iter()
+            .cloned()
+            .find(|world| world.id() == id)
+            .ok_or(TryFromWorldIdError(id))
+    }
+}
+
+impl fmt::Display for World {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for World {
+    type Err = ParseWorldError;
+
+    /// Parses a world by its case-insensitive name.
+    fn from_str(s: &str) -> Result<World, ParseWorldError> {
+        World::ALL
+            .iter()
+            .cloned()
+            .find(|world| world.name().eq_ignore_ascii_case(s))
+            .ok_or(ParseWorldError(()))
+    }
+}
+
+/// Serializes as the string-encoded world id, like the push API does.
+impl serde::Serialize for World {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.id())
+    }
+}
+
+/// Deserializes from a world id, either string-encoded or as a number, or from a world name.
+impl<'de> serde::Deserialize<'de> for World {
+    fn deserialize<D>(deserializer: D) -> Result<World, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct WorldVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for WorldVisitor {
+            type Value = World;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a world id or name")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<World, E>
+            where
+                E: serde::de::Error,
+            {
+                World::try_from(WorldId::new(value)).map_err(E::custom)
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<World, E>
+            where
+                E: serde::de::Error,
+            {
+                match value.parse::<WorldId>() {
+                    Ok(id) => World::try_from(id).map_err(E::custom),
+                    Err(_) => value.parse().map_err(E::custom),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(WorldVisitor)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TryFromWorldIdError(WorldId);
+
+impl fmt::Display for TryFromWorldIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown world id {}", self.0)
+    }
+}
+
+impl ::std::error::Error for TryFromWorldIdError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseWorldError(());
+
+impl fmt::Display for ParseWorldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unknown world name")
+    }
+}
+
+impl ::std::error::Error for ParseWorldError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn try_from_world_id() {
+        assert_eq!(World::try_from(WorldId::new(40)), Ok(World::Soltech));
+        assert_eq!(World::try_from(WorldId::new(2002)), Ok(World::Rashnu));
+        assert!(World::try_from(WorldId::new(2)).is_err());
+
+        for world in World::ALL.iter() {
+            assert_eq!(World::try_from(world.id()), Ok(*world));
+        }
+    }
+
+    #[test]
+    fn fromstr_display() {
+        assert_eq!("Genudine".parse(), Ok(World::Genudine));
+        assert_eq!("genudine".parse(), Ok(World::Genudine));
+        assert!("Genudin".parse::<World>().is_err());
+
+        for world in World::ALL.iter() {
+            assert_eq!(world.to_string().parse(), Ok(*world));
+        }
+    }
+
+    #[test]
+    fn environment() {
+        assert_eq!(World::Soltech.environment(), Environment::Pc);
+        assert_eq!(World::Xelas.environment(), Environment::Ps4Us);
+        assert_eq!(World::Ceres.environment(), Environment::Ps4Eu);
+    }
+
+    #[test]
+    fn serde() {
+        assert_eq!(serde_json::to_value(World::Cobalt).unwrap(), json!("13"));
+
+        assert_eq!(serde_json::from_value::<World>(json!("13")).unwrap(), World::Cobalt);
+        assert_eq!(serde_json::from_value::<World>(json!(13)).unwrap(), World::Cobalt);
+        assert_eq!(serde_json::from_value::<World>(json!("Cobalt")).unwrap(), World::Cobalt);
+        assert!(serde_json::from_value::<World>(json!("2")).is_err());
+    }
+}
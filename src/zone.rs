@@ -0,0 +1,239 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use serde;
+
+use id::IdVisitor;
+
+/// A raw `zone_id` as carried by events.
+///
+/// The low 16 bits are the zone definition (e.g. Indar), the high bits are the instance id of
+/// instanced zones such as Desolation or Koltyr. Non-instanced zones have instance id `0`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct ZoneId(u64);
+
+impl ZoneId {
+    pub fn new(zone_id: u64) -> ZoneId {
+        ZoneId(zone_id)
+    }
+
+    pub fn from_parts(definition_id: u16, instance_id: u64) -> ZoneId {
+        ZoneId(instance_id << 16 | u64::from(definition_id))
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+
+    pub fn definition_id(self) -> u16 {
+        (self.0 & 0xffff) as u16
+    }
+
+    pub fn instance_id(self) -> u64 {
+        self.0 >> 16
+    }
+
+    pub fn is_instanced(self) -> bool {
+        self.instance_id() != 0
+    }
+
+    /// The known zone of this id's definition, regardless of its instance.
+    pub fn zone(self) -> Option<Zone> {
+        Zone::from_definition_id(self.definition_id())
+    }
+}
+
+impl From<Zone> for ZoneId {
+    fn from(zone: Zone) -> ZoneId {
+        ZoneId::from_parts(zone.definition_id(), 0)
+    }
+}
+
+impl fmt::Display for ZoneId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Serializes as a string, like the push API does.
+impl serde::Serialize for ZoneId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.0)
+    }
+}
+
+/// Deserializes from a string or a number.
+impl<'de> serde::Deserialize<'de> for ZoneId {
+    fn deserialize<D>(deserializer: D) -> Result<ZoneId, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(IdVisitor).map(ZoneId)
+    }
+}
+
+impl FromStr for ZoneId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<ZoneId, ParseIntError> {
+        s.parse().map(ZoneId)
+    }
+}
+
+/// The known zone definitions.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum Zone {
+    Indar,
+    Hossin,
+    Amerish,
+    Esamir,
+    Oshur,
+    Koltyr,
+    /// The per-faction VR training zones.
+    VrTraining,
+    Sanctuary,
+    Desolation,
+    Tutorial,
+}
+
+impl Zone {
+    pub const ALL: [Zone; 10] = [
+        Zone::Indar,
+        Zone::Hossin,
+        Zone::Amerish,
+        Zone::Esamir,
+        Zone::Oshur,
+        Zone::Koltyr,
+        Zone::VrTraining,
+        Zone::Sanctuary,
+        Zone::Desolation,
+        Zone::Tutorial,
+    ];
+
+    pub fn from_definition_id(definition_id: u16) -> Option<Zone> {
+        use self::Zone::*;
+
+        match definition_id {
+            2 => Some(Indar),
+            4 => Some(Hossin),
+            6 => Some(Amerish),
+            8 => Some(Esamir),
+            344 => Some(Oshur),
+            14 => Some(Koltyr),
+            96..=98 => Some(VrTraining),
+            362 => Some(Sanctuary),
+            361 => Some(Desolation),
+            95 | 364 => Some(Tutorial),
+            _ => None,
+        }
+    }
+
+    /// The definition id of this zone. For zones with several definitions, this is the first.
+    pub fn definition_id(self) -> u16 {
+        use self::Zone::*;
+
+        match self {
+            Indar => 2,
+            Hossin => 4,
+            Amerish => 6,
+            Esamir => 8,
+            Oshur => 344,
+            Koltyr => 14,
+            VrTraining => 96,
+            Sanctuary => 362,
+            Desolation => 361,
+            Tutorial => 95,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        use self::Zone::*;
+
+        match self {
+            Indar => "Indar",
+            Hossin => "Hossin",
+            Amerish => "Amerish",
+            Esamir => "Esamir",
+            Oshur => "Oshur",
+            Koltyr => "Koltyr",
+            VrTraining => "VR Training",
+            Sanctuary => "Sanctuary",
+            Desolation => "Desolation",
+            Tutorial => "Tutorial",
+        }
+    }
+
+    /// Whether this is one of the permanent, lockable continents.
+    pub fn is_continent(self) -> bool {
+        use self::Zone::*;
+
+        match self {
+            Indar | Hossin | Amerish | Esamir | Oshur => true,
+            Koltyr | VrTraining | Sanctuary | Desolation | Tutorial => false,
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn continent() {
+        let zone_id = ZoneId::new(2);
+        assert_eq!(zone_id.definition_id(), 2);
+        assert_eq!(zone_id.instance_id(), 0);
+        assert!(!zone_id.is_instanced());
+        assert_eq!(zone_id.zone(), Some(Zone::Indar));
+        assert_eq!(ZoneId::from(Zone::Indar), zone_id);
+    }
+
+    #[test]
+    fn instanced() {
+        let zone_id: ZoneId = "196969".parse().unwrap();
+        assert_eq!(zone_id.definition_id(), 361);
+        assert_eq!(zone_id.instance_id(), 3);
+        assert!(zone_id.is_instanced());
+        assert_eq!(zone_id.zone(), Some(Zone::Desolation));
+        assert_eq!(ZoneId::from_parts(361, 3), zone_id);
+        assert_eq!(zone_id.to_string(), "196969");
+    }
+
+    #[test]
+    fn serde() {
+        assert_eq!(serde_json::to_value(ZoneId::new(196969)).unwrap(), json!("196969"));
+
+        assert_eq!(
+            serde_json::from_value::<ZoneId>(json!("196969")).unwrap(),
+            ZoneId::from_parts(361, 3)
+        );
+        assert_eq!(
+            serde_json::from_value::<ZoneId>(json!(2)).unwrap(),
+            ZoneId::from(Zone::Indar)
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(ZoneId::new(1).zone(), None);
+        assert_eq!(ZoneId::from_parts(97, 1).zone(), Some(Zone::VrTraining));
+    }
+
+    #[test]
+    fn definition_ids() {
+        for zone in Zone::ALL.iter() {
+            assert_eq!(Zone::from_definition_id(zone.definition_id()), Some(*zone));
+        }
+    }
+}